@@ -7,7 +7,7 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use std::collections::HashMap;
 
-use session::Line;
+use session::{Line, Roster, TeamEvent, LeagueMatch};
 use discipline::*;
 use config::error::Error as ConfigError;
 
@@ -37,6 +37,15 @@ pub struct MainConfig {
     pub default_discipline: String,
     pub database: DatabaseConfig,
     pub websocket: WebSocketConfig,
+    /// Known users, clubs and teams, which can be assigned to a session
+    #[serde(default)]
+    pub roster: Roster,
+    /// Team competitions, the results of the members are collected from all lines
+    #[serde(default)]
+    pub team_events: Vec<TeamEvent>,
+    /// League matches, the shooters of two teams are paired position by position
+    #[serde(default)]
+    pub league_matches: Vec<LeagueMatch>,
 }
 
 
@@ -49,6 +58,18 @@ pub struct Config {
     pub default_discipline: Discipline,
     pub database: DatabaseConfig,
     pub websocket: WebSocketConfig,
+    /// Known users, clubs and teams, which can be assigned to a session
+    #[serde(default)]
+    pub roster: Roster,
+    /// Team competitions, the results of the members are collected from all lines
+    #[serde(default)]
+    pub team_events: Vec<TeamEvent>,
+    /// League matches, the shooters of two teams are paired position by position
+    #[serde(default)]
+    pub league_matches: Vec<LeagueMatch>,
+    /// Training games, which can be started in a session
+    #[serde(default)]
+    pub games: HashMap<String, Game>,
 }
 
 impl Config {
@@ -58,6 +79,7 @@ impl Config {
         
         let targets = Config::parse_targets(modes_dir.join("targets/"))?;
         let disciplines = Config::parse_disciplines(modes_dir.join("disciplines/"), targets)?;
+        let games = Config::parse_games(modes_dir.join("games/"))?;
         
         let config = Config::parse_config(config_dir.to_path_buf(), &disciplines)?;
         let default_discipline = Config::get_default_discipline(config.default_discipline, &disciplines)?;
@@ -75,6 +97,10 @@ impl Config {
             default_discipline,
             database: config.database,
             websocket: config.websocket,
+            roster: config.roster,
+            team_events: config.team_events,
+            league_matches: config.league_matches,
+            games,
         })
     }
 
@@ -87,6 +113,10 @@ impl Config {
         }
     }
 
+    pub fn get_game(&self, name: &str) -> Option<Game> {
+        self.games.get(name).cloned()
+    }
+
 
 
 
@@ -187,10 +217,52 @@ impl Config {
         return Ok(targets);
     }
 
+    /// Parse the games in the given dir, the dir is optional
+    fn parse_games(path: PathBuf) -> Result<HashMap<String, Game>> {
+        let mut games: HashMap<String, Game> = HashMap::new();
+        if !path.is_dir() {
+            return Ok(games);
+        }
+        for dir_entry in fs::read_dir(path)? {
+            if let Ok(path) = dir_entry {
+                match Config::parse_game(path.path()) {
+                    Ok((filename, game)) => {
+                        games.insert(filename, game);
+                    },
+                    Err(err) => {
+                        return Err(ConfigError::GameParsing(path.path(), Box::new(err)));
+                    },
+                }
+            }
+        }
+        Ok(games)
+    }
+
+    fn parse_game(path: PathBuf) -> Result<(String, Game)> {
+        let filename = path.file_stem().unwrap().to_str().unwrap().to_string();
+        let raw_json = Config::read_file(path)?;
+        let game: Game = serde_json::from_str(&raw_json)?;
+        game.validate().map_err(ConfigError::InvalidGame)?;
+        Ok((filename, game))
+    }
+
+    /// Load a single target definition, e.g. for scoring without a running client
+    /// modes_dir:  modes dir, the target is searched in its targets dir
+    /// name:       name of the target file without suffix (e.g. "lg"), or the path to a target file
+    pub fn load_target(modes_dir: &Path, name: &str) -> Result<Target> {
+        let path = Path::new(name);
+        let path = match path.is_file() {
+            true => path.to_path_buf(),
+            false => modes_dir.join("targets").join(format!("{}.json", name)),
+        };
+        Config::parse_target(path).map(|(_, target)| target)
+    }
+
     fn parse_target(path: PathBuf) -> Result<(String, Target)> {
         let filename = path.file_stem().unwrap().to_str().unwrap().to_string();
         let raw_json = Config::read_file(path)?;
         let target: Target = serde_json::from_str(&raw_json)?;
+        target.validate().map_err(ConfigError::InvalidTarget)?;
         Ok((filename, target))
     }
 }
@@ -16,6 +16,9 @@ pub enum Error {
     JSONParsing(JSONError),
     DisciplineParsing(PathBuf, Box<Error>),
     TargetParsing(PathBuf, Box<Error>),
+    GameParsing(PathBuf, Box<Error>),
+    InvalidTarget(DisciplineError),
+    InvalidGame(DisciplineError),
 }
 
 impl error::Error for Error {
@@ -33,6 +36,12 @@ impl error::Error for Error {
                 "Error parsing discipline json file",
             Error::TargetParsing(_, _) =>
                 "Error parsing target json file",
+            Error::GameParsing(_, _) =>
+                "Error parsing game json file",
+            Error::InvalidTarget(_) =>
+                "Invalid target definition",
+            Error::InvalidGame(_) =>
+                "Invalid game definition",
         }
     }
 
@@ -44,6 +53,9 @@ impl error::Error for Error {
             Error::JSONParsing(ref e) => Some(e),
             Error::DisciplineParsing(_, ref e) => Some(e),
             Error::TargetParsing(_, ref e) => Some(e),
+            Error::GameParsing(_, ref e) => Some(e),
+            Error::InvalidTarget(ref e) => Some(e),
+            Error::InvalidGame(ref e) => Some(e),
         }
     }
 }
@@ -63,6 +75,12 @@ impl fmt::Display for Error {
                 write!(f, "Error parsing discipline json at path {:?}: {}", path, err),
             Error::TargetParsing(ref path, ref err) =>
                 write!(f, "Error parsing target json at path {:?}: {}", path, err),
+            Error::GameParsing(ref path, ref err) =>
+                write!(f, "Error parsing game json at path {:?}: {}", path, err),
+            Error::InvalidTarget(ref err) =>
+                write!(f, "Invalid target definition: {}", err),
+            Error::InvalidGame(ref err) =>
+                write!(f, "Invalid game definition: {}", err),
         }
 
     }
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use session::Session;
+
+
+
+/// Filter for stored session queries, all given values have to match
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SessionFilter {
+    /// Only sessions created at or after this date
+    #[serde(default)]
+    pub since: Option<SystemTime>,
+    /// Tags the session has to have, with the same value
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    #[serde(default)]
+    pub rifle: Option<String>,
+    #[serde(default)]
+    pub ammo_lot: Option<String>,
+}
+
+impl SessionFilter {
+    /// Check if the given session matches the filter
+    pub fn matches(&self, session: &Session) -> bool {
+        let equipment = &session.info.equipment;
+        self.since.map_or(true, |since| session.get_created() >= since) &&
+            self.tags.iter().all(|(key, value)| session.info.tags.get(key) == Some(value)) &&
+            (self.rifle.is_none() || equipment.rifle == self.rifle) &&
+            (self.ammo_lot.is_none() || equipment.ammo_lot == self.ammo_lot)
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::time::{SystemTime, Duration};
+    use database::filter::*;
+    use session::{Session, Line, Update, Equipment};
+    use helper;
+
+    #[test]
+    fn test_matches() {
+        let discipline = helper::dsc_demo::lg_discipline();
+        let mut session = Session::new("0".to_string(), Line::demo(), discipline);
+        session.set_tag("barrel".to_string(), Some("A".to_string()));
+        session.set_equipment(Equipment {
+            rifle: Some("LG 400".to_string()),
+            ammo_lot: Some("1234".to_string()),
+            pellet_weight: Some(0.53),
+            temperature: None,
+        });
+
+        assert!(SessionFilter::default().matches(&session));
+
+        let mut filter = SessionFilter::default();
+        filter.tags.insert("barrel".to_string(), "A".to_string());
+        filter.ammo_lot = Some("1234".to_string());
+        assert!(filter.matches(&session));
+
+        filter.rifle = Some("LG 300".to_string());
+        assert!(!filter.matches(&session));
+
+        let mut filter = SessionFilter::default();
+        filter.tags.insert("barrel".to_string(), "B".to_string());
+        assert!(!filter.matches(&session));
+
+        let mut filter = SessionFilter::default();
+        filter.since = Some(SystemTime::now() + Duration::from_secs(60));
+        assert!(!filter.matches(&session));
+
+        // Removed tags do no longer match
+        session.set_tag("barrel".to_string(), None);
+        let mut filter = SessionFilter::default();
+        filter.tags.insert("barrel".to_string(), "A".to_string());
+        assert!(!filter.matches(&session));
+    }
+}
@@ -1,13 +1,14 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde_json;
 use std::fs::File;
 use std::io::prelude::*;
-use std::time::SystemTime;
 
 // use std::error::Error;
 use time::OffsetDateTime;
 
 use session::Session;
+use registry::Registry;
+use super::filter::SessionFilter;
 use std::fs;
 
 
@@ -21,7 +22,30 @@ pub trait DBHandler {
     // Update given session object in database
     fn update_sesssion(&self, session: &Session);
     
-    fn get_stored_sessions(&self, since: SystemTime) -> Vec<Session>;
+    // Load all stored sessions matching the given filter, oldest first
+    fn get_stored_sessions(&self, filter: &SessionFilter) -> Vec<Session>;
+
+    // Load the session with the given id, None if it does not exist
+    fn get_session(&self, session_id: &str) -> Option<Session>;
+
+    // Load the last session of the given line, if it was not finished (e.g. after a crash)
+    fn get_unfinished_session(&self, line_id: i32) -> Option<Session>;
+
+    // Load all finalized sessions of the given line, in the order they were finalized
+    fn get_finalized_sessions(&self, line_id: i32) -> Vec<Session>;
+
+    // Load the hash of the last finalized session of the given line, None if no session was
+    // finalized yet
+    fn get_last_hash(&self, line_id: i32) -> Option<String>;
+
+    // Store the hash of the last finalized session of the given line
+    fn update_last_hash(&self, line_id: i32, hash: &str);
+
+    // Load the registry of all shooters, empty if nothing is stored yet
+    fn get_registry(&self) -> Registry;
+
+    // Store the given registry
+    fn update_registry(&self, registry: &Registry);
 }
 
 
@@ -45,9 +69,33 @@ impl DBHandler for DBHandlerNone {
     }
     fn update_sesssion(&self, _session: &Session) {}
     
-    fn get_stored_sessions(&self, since: SystemTime) -> Vec<Session> {
+    fn get_stored_sessions(&self, _filter: &SessionFilter) -> Vec<Session> {
         return vec![];
     }
+
+    fn get_session(&self, _session_id: &str) -> Option<Session> {
+        return None;
+    }
+
+    fn get_unfinished_session(&self, _line_id: i32) -> Option<Session> {
+        return None;
+    }
+
+    fn get_finalized_sessions(&self, _line_id: i32) -> Vec<Session> {
+        return vec![];
+    }
+
+    fn get_last_hash(&self, _line_id: i32) -> Option<String> {
+        return None;
+    }
+
+    fn update_last_hash(&self, _line_id: i32, _hash: &str) {}
+
+    fn get_registry(&self) -> Registry {
+        return Registry::default();
+    }
+
+    fn update_registry(&self, _registry: &Registry) {}
 }
 
 
@@ -64,6 +112,56 @@ impl DBHandlerFileSystem {
     pub fn new(path: String) -> DBHandlerFileSystem {
         DBHandlerFileSystem{ path: PathBuf::from(path) }
     }
+
+    // Return the paths of all session files in the given directory and its subdirectories
+    fn find_session_files(path: &Path) -> Vec<PathBuf> {
+        let mut files = vec![];
+        let entries = match fs::read_dir(path) {
+            Err(_) => return files,
+            Ok(entries) => entries,
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let entry_path = entry.path();
+            if entry_path.is_dir() {
+                files.extend(DBHandlerFileSystem::find_session_files(&entry_path));
+            }
+            else if entry_path.extension().map_or(false, |suffix| suffix == "dscSession") {
+                files.push(entry_path);
+            }
+        }
+        return files;
+    }
+
+    // Return the path of the file with the hash of the last finalized session of the given line
+    fn last_hash_path(&self, line_id: i32) -> PathBuf {
+        let mut path = self.path.clone();
+        path.push(format!("Line_{}.lastHash", line_id));
+        path
+    }
+
+    // Read and parse the session file at the given path, all values are derived from the stored
+    // event log
+    fn read_session(session_path: &Path) -> Option<Session> {
+        let mut file = match File::open(session_path) {
+            Err(_) => return None,
+            Ok(file) => file,
+        };
+        let mut text = String::new();
+        if let Err(why) = file.read_to_string(&mut text) {
+            println!("couldn't read {:?}: {:?}", session_path, why);
+            return None;
+        }
+        match serde_json::from_str::<Session>(&text) {
+            Ok(mut session) => {
+                session.rebuild();
+                Some(session)
+            },
+            Err(why) => {
+                println!("couldn't parse {:?}: {:?}", session_path, why);
+                None
+            },
+        }
+    }
 }
 impl DBHandler for DBHandlerFileSystem {
     // TODO use i64, and use timestamp in ns
@@ -110,7 +208,175 @@ impl DBHandler for DBHandlerFileSystem {
         }
     }
     
-    fn get_stored_sessions(&self, since: SystemTime) -> Vec<Session> {
-        return vec![];
+    fn get_stored_sessions(&self, filter: &SessionFilter) -> Vec<Session> {
+        let mut sessions: Vec<Session> = DBHandlerFileSystem::find_session_files(&self.path).iter()
+            .filter_map(|path| DBHandlerFileSystem::read_session(path))
+            .filter(|session| filter.matches(session))
+            .collect();
+        sessions.sort_by_key(|session| session.get_created());
+        return sessions;
+    }
+
+    fn get_session(&self, session_id: &str) -> Option<Session> {
+        if session_id.contains(".") {
+            return None;
+        }
+
+        let mut session_path = self.path.clone();
+        session_path.push(format!("{sessionID}.{suffix}", sessionID = session_id, suffix = r"dscSession"));
+        return DBHandlerFileSystem::read_session(&session_path);
+    }
+
+    fn get_unfinished_session(&self, line_id: i32) -> Option<Session> {
+        // The last modified session file of this line
+        let prefix = format!("Line_{}_", line_id);
+        let last_session_path = DBHandlerFileSystem::find_session_files(&self.path).into_iter()
+            .filter(|path| path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with(&prefix))
+            )
+            .filter_map(|path| match fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                Ok(modified) => Some((modified, path)),
+                Err(_) => None,
+            })
+            .max_by_key(|&(modified, _)| modified)
+            .map(|(_, path)| path);
+
+        match last_session_path {
+            Some(path) => match DBHandlerFileSystem::read_session(&path) {
+                Some(ref session) if session.finished => None,
+                session => session,
+            },
+            None => None,
+        }
+    }
+
+    fn get_finalized_sessions(&self, line_id: i32) -> Vec<Session> {
+        let prefix = format!("Line_{}_", line_id);
+        let mut sessions: Vec<Session> = DBHandlerFileSystem::find_session_files(&self.path).iter()
+            .filter(|path| path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with(&prefix))
+            )
+            .filter_map(|path| DBHandlerFileSystem::read_session(path))
+            .filter(|session| session.finalization.is_some())
+            .collect();
+        sessions.sort_by_key(|session| session.finalization.as_ref().map(|finalization| finalization.date));
+        return sessions;
+    }
+
+    fn get_last_hash(&self, line_id: i32) -> Option<String> {
+        let mut text = String::new();
+        match File::open(self.last_hash_path(line_id)) {
+            Ok(mut file) => match file.read_to_string(&mut text) {
+                Ok(_) => Some(text.trim().to_string()),
+                Err(why) => {
+                    println!("couldn't read last hash of line {}: {:?}", line_id, why);
+                    None
+                },
+            },
+            // Sessions finalized before the hash was stored, we search them once
+            Err(_) => {
+                let hash = self.get_finalized_sessions(line_id).last()
+                    .and_then(|session| session.finalization.as_ref())
+                    .map(|finalization| finalization.hash.clone());
+                if let Some(ref hash) = hash {
+                    self.update_last_hash(line_id, hash);
+                }
+                hash
+            },
+        }
+    }
+
+    fn update_last_hash(&self, line_id: i32, hash: &str) {
+        match fs::create_dir_all(&self.path) {
+            Err(why) => panic!("couldn't create: {:?}", why),
+            Ok(_) => (),
+        };
+
+        let path = self.last_hash_path(line_id);
+        let mut file = match File::create(&path) {
+            Err(why) => panic!("couldn't create: {:?}", why),
+            Ok(file) => file,
+        };
+        match file.write_all(hash.as_bytes()) {
+            Err(why) => panic!("couldn't write to: {:?}", why),
+            Ok(_) => (),
+        }
+    }
+
+    fn get_registry(&self) -> Registry {
+        let mut registry_path = self.path.clone();
+        registry_path.push("registry.json");
+
+        let mut file = match File::open(&registry_path) {
+            Err(_) => return Registry::default(),
+            Ok(file) => file,
+        };
+        let mut text = String::new();
+        if let Err(why) = file.read_to_string(&mut text) {
+            println!("couldn't read {:?}: {:?}", registry_path, why);
+            return Registry::default();
+        }
+        match serde_json::from_str::<Registry>(&text) {
+            Ok(registry) => registry,
+            Err(why) => {
+                println!("couldn't parse {:?}: {:?}", registry_path, why);
+                Registry::default()
+            },
+        }
+    }
+
+    fn update_registry(&self, registry: &Registry) {
+        match fs::create_dir_all(&self.path) {
+            Err(why) => panic!("couldn't create: {:?}", why),
+            Ok(_) => (),
+        };
+
+        let mut registry_path = self.path.clone();
+        registry_path.push("registry.json");
+        let mut file = match File::create(&registry_path) {
+            Err(why) => panic!("couldn't create: {:?}", why),
+            Ok(file) => file,
+        };
+
+        let text = serde_json::to_string(&registry).unwrap();
+        match file.write_all(text.as_bytes()) {
+            Err(why) => panic!("couldn't write to: {:?}", why),
+            Ok(_) => (),
+        }
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use database::handler::*;
+    use session::{Line, ShotRaw, AddShotRaw};
+    use helper;
+
+    #[test]
+    fn test_last_hash() {
+        let path = env::temp_dir().join("dsc_test_last_hash");
+        let _ = fs::remove_dir_all(&path);
+        let db_handler = DBHandlerFileSystem::new(path.to_str().unwrap().to_string());
+        assert_eq!(None, db_handler.get_last_hash(1));
+
+        // Sessions finalized before the hash was stored are searched
+        let mut session = Session::new("Line_1_a".to_string(), Line::demo(), helper::dsc_demo::lg_discipline());
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        assert!(session.finalize("Officer".to_string(), None));
+        db_handler.update_sesssion(&session);
+        let hash = session.finalization.clone().unwrap().hash;
+        assert_eq!(Some(hash.clone()), db_handler.get_last_hash(1));
+
+        db_handler.update_last_hash(1, "abc");
+        assert_eq!(Some("abc".to_string()), db_handler.get_last_hash(1));
+        assert_eq!(None, db_handler.get_last_hash(2));
+        let _ = fs::remove_dir_all(&path);
     }
 }
@@ -1 +1,3 @@
+pub mod filter;
 pub mod handler;
+pub mod rescore;
@@ -0,0 +1,120 @@
+use discipline::Target;
+use session::{RescoreReport, rescore};
+use super::handler::DBHandler;
+use super::filter::SessionFilter;
+
+
+
+// Reports of scoring the stored sessions again
+#[derive(Debug, Clone, Default)]
+pub struct StoredRescore {
+    // Sessions with changed scores, saved unless it was a dry run
+    pub changed: Vec<RescoreReport>,
+    // Finalized sessions with changed scores, they are never saved, since this would break their
+    // sign-off, and need a manual review
+    pub needs_review: Vec<RescoreReport>,
+}
+
+// Score all stored sessions of the given target again, e.g. after a mistake in the target
+// definition was corrected. Sessions are selected by the title of their target, so the corrected
+// definition has to keep its title. Changed sessions get a note about the changes, finalized
+// sessions are left unchanged.
+// db_handler:  store to read the sessions from and to write them back
+// target:      corrected target definition
+// filter:      only sessions matching the filter are scored again
+// write:       if false, only the report is created and no session is changed
+// return:      reports of all sessions with changed scores
+pub fn rescore_stored_sessions(db_handler: &dyn DBHandler, target: &Target, filter: &SessionFilter, write: bool) -> StoredRescore {
+    let mut result = StoredRescore::default();
+    for session in db_handler.get_stored_sessions(filter) {
+        if session.discipline.target.title != target.title {
+            continue;
+        }
+        let (rescored, report) = rescore(&session, target);
+        if report.is_empty() {
+            continue;
+        }
+        if session.finalization.is_some() {
+            result.needs_review.push(report);
+            continue;
+        }
+        if write {
+            db_handler.update_sesssion(&rescored);
+        }
+        result.changed.push(report);
+    }
+    result
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+    use database::rescore::*;
+    use database::handler::DBHandlerFileSystem;
+    use session::{Session, Line, ShotRaw, AddShotRaw, verify_chain};
+    use helper;
+
+    #[test]
+    fn test_rescore_stored_sessions() {
+        let path = env::temp_dir().join("dsc_test_rescore_stored_sessions");
+        let _ = fs::remove_dir_all(&path);
+        let db_handler = DBHandlerFileSystem::new(path.to_str().unwrap().to_string());
+
+        let mut session = Session::new("a".to_string(), Line::demo(), helper::dsc_demo::lg_discipline());
+        session.add_shot_raw(ShotRaw { x: 2501, y: 0 });
+        db_handler.update_sesssion(&session);
+        let mut other = helper::dsc_demo::lg_discipline();
+        other.target.title = "Other".to_string();
+        db_handler.update_sesssion(&Session::new("b".to_string(), Line::demo(), other));
+
+        let mut target = helper::dsc_demo::lg_target();
+        target.bullet_diameter = 4.6;
+        let filter = SessionFilter::default();
+
+        let reports = rescore_stored_sessions(&db_handler, &target, &filter, false).changed;
+        assert_eq!(1, reports.len());
+        assert_eq!("a", reports[0].session_id);
+        assert!(db_handler.get_session("a").unwrap().rescores.is_empty());
+
+        rescore_stored_sessions(&db_handler, &target, &filter, true);
+        let stored = db_handler.get_session("a").unwrap();
+        assert_eq!(1, stored.rescores.len());
+        assert_eq!(4.6, stored.discipline.target.bullet_diameter);
+        assert_eq!(reports[0].new_result, stored.get_result());
+
+        // Nothing changes anymore
+        assert!(rescore_stored_sessions(&db_handler, &target, &filter, true).changed.is_empty());
+        let _ = fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn test_rescore_finalized_session() {
+        let path = env::temp_dir().join("dsc_test_rescore_finalized_session");
+        let _ = fs::remove_dir_all(&path);
+        let db_handler = DBHandlerFileSystem::new(path.to_str().unwrap().to_string());
+
+        let mut session = Session::new("a".to_string(), Line::demo(), helper::dsc_demo::lg_discipline());
+        session.add_shot_raw(ShotRaw { x: 2501, y: 0 });
+        assert!(session.finalize("Officer".to_string(), None));
+        db_handler.update_sesssion(&session);
+
+        let mut target = helper::dsc_demo::lg_target();
+        target.bullet_diameter = 4.6;
+        let result = rescore_stored_sessions(&db_handler, &target, &SessionFilter::default(), true);
+        assert!(result.changed.is_empty());
+        assert_eq!(1, result.needs_review.len());
+        assert_eq!("a", result.needs_review[0].session_id);
+
+        // The stored session and its sign-off are unchanged
+        let stored = db_handler.get_session("a").unwrap();
+        assert!(stored.rescores.is_empty());
+        assert_eq!(4.5, stored.discipline.target.bullet_diameter);
+        assert!(verify_chain(&[stored]).is_ok());
+        let _ = fs::remove_dir_all(&path);
+    }
+}
@@ -4,6 +4,9 @@ use super::interface::Interface;
 use super::target::Target;
 use super::part::DisciplinePart;
 use super::time::Time;
+use super::tie_break::TieBreak;
+use super::sight::Sight;
+use super::face::{TargetFace, get_face};
 use super::error::Error as DisciplineError;
 
 
@@ -16,6 +19,15 @@ pub struct Discipline {
     pub time: Time,
     pub target: Target,
     pub parts: Vec<DisciplinePart>,
+    /// Rules to rank sessions with the same result
+    #[serde(default = "TieBreak::default_rules")]
+    pub tie_break: Vec<TieBreak>,
+    /// Default sight, used for sight corrections if the shooter has no own sight
+    #[serde(default)]
+    pub sight: Option<Sight>,
+    /// Faces of the lane, empty if the lane has a single face
+    #[serde(default)]
+    pub faces: Vec<TargetFace>,
 }
 
 impl Discipline {
@@ -28,6 +40,20 @@ impl Discipline {
         }
         return None;
     }
+
+    /// Return the main part of the discipline, if there is no main part we use the first one
+    pub fn get_main_part(&self) -> Option<&DisciplinePart> {
+        match self.parts.iter().find(|part| part.main_part) {
+            Some(part) => Some(part),
+            None => self.parts.first(),
+        }
+    }
+
+    /// Return the face hit by a shot at the given lane coordinates
+    /// return:     index of the face, and the coordinates relative to the centre of the face
+    pub fn get_face(&self, x: i32, y: i32) -> (usize, i32, i32) {
+        get_face(&self.faces, x, y)
+    }
 }
 
 
@@ -41,6 +67,12 @@ pub struct DisciplineConfig {
     time: Time,
     target_name: String,
     parts: Vec<DisciplinePart>,
+    #[serde(default = "TieBreak::default_rules")]
+    tie_break: Vec<TieBreak>,
+    #[serde(default)]
+    sight: Option<Sight>,
+    #[serde(default)]
+    faces: Vec<TargetFace>,
 }
 
 impl DisciplineConfig {
@@ -53,6 +85,9 @@ impl DisciplineConfig {
                 time: config.time,
                 target: target.clone(),
                 parts: config.parts,
+                tie_break: config.tie_break,
+                sight: config.sight,
+                faces: config.faces,
             }),
             None => Err(DisciplineError::TargetNotFound),
         }
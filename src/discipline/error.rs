@@ -6,12 +6,26 @@ use std::fmt;
 #[derive(Debug)]
 pub enum Error {
     TargetNotFound,
+    NoRings,
+    InvalidBulletDiameter,
+    InvalidRingWidth(i32),
+    RingsNotSorted(i32),
+    InvalidMaxRing,
+    InvalidTeilerFactor,
+    InvalidGameCountMode,
 }
 
 impl StdError for Error {
     fn description(&self) -> &str {
         match *self {
             Error::TargetNotFound => "Target not found",
+            Error::NoRings => "Target has no rings",
+            Error::InvalidBulletDiameter => "Invalid bullet diameter",
+            Error::InvalidRingWidth(_) => "Invalid ring width",
+            Error::RingsNotSorted(_) => "Rings not sorted",
+            Error::InvalidMaxRing => "Invalid max ring",
+            Error::InvalidTeilerFactor => "Invalid teiler factor",
+            Error::InvalidGameCountMode => "Invalid count mode of game",
         }
     }
 }
@@ -19,6 +33,18 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::TargetNotFound => write!(f, "Target not found"),
+            Error::NoRings => write!(f, "Target has no rings"),
+            Error::InvalidBulletDiameter => write!(f, "Bullet diameter must not be negative"),
+            Error::InvalidRingWidth(value) =>
+                write!(f, "Ring {} has to be wider than 0", value),
+            Error::RingsNotSorted(value) =>
+                write!(f, "Ring {} is not sorted, rings have to be ordered from the innermost to the outermost", value),
+            Error::InvalidMaxRing =>
+                write!(f, "Max ring has to be at least the value of the innermost ring"),
+            Error::InvalidTeilerFactor =>
+                write!(f, "Teiler factor has to be greater than 0"),
+            Error::InvalidGameCountMode =>
+                write!(f, "A score race has to be counted in rings, lower teilers can not reach a target score"),
         }
     }
 }
@@ -0,0 +1,43 @@
+/// Target face of a lane with several faces, e.g. the five faces of 25m rapid fire. All faces
+/// use the target of the discipline.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TargetFace {
+    pub name: String,
+    /// Centre of the face in 1/1000 mm, relative to the centre of the lane
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Return the face nearest to the given lane coordinates
+/// faces:      faces of the lane, a lane without faces has a single face in its centre
+/// x, y:       coordinates of the shot in 1/1000 mm, relative to the centre of the lane
+/// return:     index of the face, and the coordinates relative to the centre of the face
+pub fn get_face(faces: &[TargetFace], x: i32, y: i32) -> (usize, i32, i32) {
+    let distance = |face: &TargetFace| {
+        (i64::from(x) - i64::from(face.x)).pow(2) + (i64::from(y) - i64::from(face.y)).pow(2)
+    };
+    match faces.iter().enumerate().min_by_key(|&(_, face)| distance(face)) {
+        Some((index, face)) => (index, x - face.x, y - face.y),
+        None => (0, x, y),
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use discipline::face::*;
+
+    #[test]
+    fn test_get_face() {
+        let faces: Vec<TargetFace> = (0..5)
+            .map(|i| TargetFace { name: format!("{}", i + 1), x: (i - 2) * 750_000, y: 0 })
+            .collect();
+        assert_eq!((2, 1000, -2000), get_face(&faces, 1000, -2000));
+        assert_eq!((0, 10_000, 0), get_face(&faces, -1_490_000, 0));
+        assert_eq!((4, -100_000, 5000), get_face(&faces, 1_400_000, 5000));
+        assert_eq!((0, 1000, 2000), get_face(&[], 1000, 2000));
+    }
+}
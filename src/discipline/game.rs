@@ -0,0 +1,84 @@
+use session::{CountMode, Score};
+use super::error::Error as DisciplineError;
+
+
+
+/// Training game, selectable like a discipline. The game runs on top of the session and is
+/// played with its counted shots.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Game {
+    pub id: String,
+    pub title: String,
+    pub rules: GameRules,
+}
+
+/// Rules and end conditions of a game
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum GameRules {
+    /// Hit the sectors of the target in order, like the hours of a clock. The first sector starts
+    /// at 12 o'clock, the others follow clockwise. A hit counts, if it is in the next sector and
+    /// reaches the min ring. The game ends when all sectors were hit.
+    AroundTheClock { sectors: usize, min_ring: Score },
+    /// Each shot has to reach the ring of the next step, the first shot below ends the game
+    EliminationLadder { steps: Vec<Score> },
+    /// Reach the target score with as few shots as possible, the shots are counted with the
+    /// count mode of the game, which has to be a ring count mode
+    ScoreRace { target_score: Score, count_mode: CountMode },
+    /// Best single shot of the given number of shots
+    BestOf { number_of_shots: usize },
+}
+
+impl Game {
+    /// Check the rules of the game, a score race can only be counted in rings, since lower
+    /// teilers are better and the lowest teiler does not add up
+    pub fn validate(&self) -> Result<(), DisciplineError> {
+        match self.rules {
+            GameRules::ScoreRace { count_mode, .. } if count_mode.is_teiler() =>
+                Err(DisciplineError::InvalidGameCountMode),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Return the sector of the target for the given angle, see GameRules::AroundTheClock
+/// angle:      angle of the shot in degrees, counterclockwise from 3 o'clock
+/// sectors:    number of sectors
+/// return:     index of the sector, 0 is the sector right of 12 o'clock
+pub fn get_sector(angle: f64, sectors: usize) -> usize {
+    let sectors = sectors.max(1);
+    let clockwise = (450_f64 - angle) % 360_f64;
+    (clockwise / (360_f64 / sectors as f64)) as usize % sectors
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use discipline::game::*;
+
+    #[test]
+    fn test_get_sector() {
+        assert_eq!(0, get_sector(89.0, 12));
+        assert_eq!(11, get_sector(91.0, 12));
+        assert_eq!(3, get_sector(0.0, 12));
+        assert_eq!(6, get_sector(269.0, 12));
+        assert_eq!(2, get_sector(200.0, 4));
+        assert_eq!(0, get_sector(180.0, 0));
+    }
+
+    #[test]
+    fn test_validate() {
+        let game = |count_mode| Game {
+            id: "race".to_string(),
+            title: "Race".to_string(),
+            rules: GameRules::ScoreRace { target_score: Score::from_integer(100), count_mode },
+        };
+        assert!(game(CountMode::Integer).validate().is_ok());
+        assert!(game(CountMode::Tenth).validate().is_ok());
+        assert!(game(CountMode::Teiler).validate().is_err());
+        assert!(game(CountMode::TeilerSum).validate().is_err());
+    }
+}
@@ -1,13 +1,23 @@
 pub mod discipline;
 pub mod error;
+pub mod face;
+pub mod game;
 pub mod part;
 pub mod interface;
+pub mod run;
+pub mod sight;
 pub mod target;
+pub mod tie_break;
 pub mod time;
 
 pub use self::interface::Interface;
 pub use self::discipline::{Discipline, DisciplineConfig};
 pub use self::error::Error as DisciplineError;
-pub use self::part::{DisciplinePart, PartAverage, PartExitType};
+pub use self::face::TargetFace;
+pub use self::game::{Game, GameRules};
+pub use self::part::{DisciplinePart, PartAverage, PartExitType, PartMode, FinalStage};
+pub use self::run::{Run, RunSpeed, RunDirection};
+pub use self::sight::Sight;
 pub use self::target::{Target, Zoom, Ring, WebColor};
+pub use self::tie_break::TieBreak;
 pub use self::time::Time;
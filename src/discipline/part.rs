@@ -1,4 +1,5 @@
 use super::time::Time;
+use super::run::Run;
 use session::CountMode;
 
 
@@ -17,6 +18,46 @@ pub struct DisciplinePart {
     pub time: Time,
     pub average: PartAverage,
     pub exit_type: PartExitType,
+    #[serde(default)]
+    pub mode: PartMode,
+    /// Runs of a moving target, the shots of the part are assigned to the runs in this order,
+    /// starting again after the last run. Empty for fixed targets.
+    #[serde(default)]
+    pub runs: Vec<Run>,
+    /// Only one hit per face counts in each series, further hits on a face are scored as misses
+    #[serde(default)]
+    pub one_hit_per_face: bool,
+}
+
+impl DisciplinePart {
+    /// Return the stage of a final for the shot with the given index in the part, together with
+    /// the index of the first shot of the stage.
+    /// shot_index:     0 based index of the shot in the part
+    /// return:         None if the part is no final, or the shot is after the last stage
+    pub fn get_final_stage(&self, shot_index: i32) -> Option<(usize, &FinalStage, i32)> {
+        match self.mode {
+            PartMode::Final { ref stages } => {
+                let mut first_shot = 0;
+                for (i, stage) in stages.iter().enumerate() {
+                    if shot_index < first_shot + stage.number_of_shots {
+                        return Some((i, stage, first_shot));
+                    }
+                    first_shot += stage.number_of_shots;
+                }
+                None
+            },
+            PartMode::Default | PartMode::LotTest { .. } => None,
+        }
+    }
+
+    /// Return the run of the shot with the given index in the part, if the part has runs
+    /// shot_index:     0 based index of the shot in the part
+    pub fn get_run(&self, shot_index: i32) -> Option<Run> {
+        if self.runs.is_empty() || shot_index < 0 {
+            return None;
+        }
+        Some(self.runs[shot_index as usize % self.runs.len()])
+    }
 }
 
 
@@ -34,6 +75,40 @@ pub enum PartAverage {
 
 
 
+/// Mode of a part, a normal part with series of series_length, a final with stages, or a lot
+/// test, which groups the shots by the ammunition lot of the session
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum PartMode {
+    Default,
+    Final { stages: Vec<FinalStage> },
+    LotTest { group_size: usize },
+}
+
+impl Default for PartMode {
+    fn default() -> PartMode {
+        PartMode::Default
+    }
+}
+
+
+
+/// One stage of a final, e.g. 2 series of 5 shots, or 2 single shots followed by an elimination
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FinalStage {
+    pub name: String,
+    pub number_of_shots: i32,
+    pub series_length: i32,
+    /// Time limit for each series of the stage in seconds
+    pub series_time: u64,
+    /// Sighting period before the stage in seconds, None if the stage has no sighting period
+    pub sighting_time: Option<u64>,
+    /// Number of shooters eliminated after this stage, if several lines participate
+    pub eliminations: usize,
+}
+
+
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum PartExitType {
     Always,
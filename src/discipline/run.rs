@@ -0,0 +1,20 @@
+/// Speed of a run of a moving target
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum RunSpeed {
+    Slow,
+    Fast,
+}
+
+/// Direction a moving target runs in, seen from the shooter
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum RunDirection {
+    Left,
+    Right,
+}
+
+/// Run of a moving target (Laufende Scheibe) in which a shot was fired
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Run {
+    pub speed: RunSpeed,
+    pub direction: RunDirection,
+}
@@ -0,0 +1,17 @@
+/// Sight of a rifle or pistol, used to calculate sight corrections
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Sight {
+    /// Movement of the point of impact per click in mm, at the reference distance
+    pub click_value: f64,
+    /// Distance in m, the click value is given for
+    pub reference_distance: f64,
+    /// Distance in m to the target
+    pub distance: f64,
+}
+
+impl Sight {
+    /// Movement of the point of impact per click in mm, at the target distance
+    pub fn get_click_value_at_target(&self) -> f64 {
+        self.click_value * self.distance / self.reference_distance
+    }
+}
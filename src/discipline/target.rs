@@ -1,9 +1,15 @@
 
 
 
+use session::Score;
+use super::error::Error as DisciplineError;
+
+
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Target {
     pub title: String,
+    /// Rings from the innermost (highest value) to the outermost one
     pub rings: Vec<Ring>,
     pub rings_draw_only: Vec<RingDrawOnly>,
     pub default_hit_color: WebColor,
@@ -12,6 +18,66 @@ pub struct Target {
     pub inner_ten: i32, // renamed innenZehner
     pub trial_corner_color: WebColor, // renamed probeEcke.color probeEcke.alpha
     pub bullet_diameter: f64, // renamed kugelDurchmesser
+    /// Highest ring value reachable with decimals (e.g. 10.9), if None we use the value of the
+    /// innermost ring + 0.9
+    pub max_ring: Option<Score>,
+    /// Factor the teiler is multiplied with in teiler count modes (e.g. for pistol targets),
+    /// if None we use 1.0
+    pub teiler_factor: Option<f64>,
+}
+
+impl Target {
+    /// Return the highest ring value reachable with decimals
+    pub fn get_max_ring(&self) -> Score {
+        match self.max_ring {
+            Some(max_ring) => max_ring,
+            None => match self.rings.first() {
+                Some(ring) => Score::from_tenths(i64::from(ring.value) * 10 + 9),
+                None => Score::zero(),
+            },
+        }
+    }
+
+    /// Return the factor for the teiler count modes
+    pub fn get_teiler_factor(&self) -> f64 {
+        self.teiler_factor.unwrap_or(1_f64)
+    }
+
+    /// Return the teiler of a miss, a shot touching the outer edge of the outermost ring, in
+    /// 1/100 mm
+    pub fn get_miss_teiler(&self) -> Score {
+        let radius = self.rings.last().map_or(0_f64, |ring| ring.width / 2_f64);
+        Score::from_tenths(((radius + self.bullet_diameter / 2_f64) * 1000_f64).round() as i64)
+    }
+
+    /// Check if the ring table can be used for scoring.
+    /// Rings have to be sorted from the innermost to the outermost ring, with increasing widths
+    /// and decreasing values.
+    pub fn validate(&self) -> Result<(), DisciplineError> {
+        if self.rings.is_empty() {
+            return Err(DisciplineError::NoRings);
+        }
+        if self.bullet_diameter < 0_f64 {
+            return Err(DisciplineError::InvalidBulletDiameter);
+        }
+        for ring in &self.rings {
+            if ring.width <= 0_f64 {
+                return Err(DisciplineError::InvalidRingWidth(ring.value));
+            }
+        }
+        for pair in self.rings.windows(2) {
+            if pair[0].width >= pair[1].width || pair[0].value <= pair[1].value {
+                return Err(DisciplineError::RingsNotSorted(pair[1].value));
+            }
+        }
+        if self.get_max_ring() < Score::from_integer(i64::from(self.rings[0].value)) {
+            return Err(DisciplineError::InvalidMaxRing);
+        }
+        if self.get_teiler_factor() <= 0_f64 {
+            return Err(DisciplineError::InvalidTeilerFactor);
+        }
+        Ok(())
+    }
 }
 
 pub type Zoom = f32;
@@ -75,3 +141,50 @@ pub struct WebColor {
 //         vec![Fig::Styled(attr, Box::new(circle))]
 //     }
 // }
+
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use discipline::*;
+    use session::Score;
+    use helper;
+
+    #[test]
+    fn test_validate() {
+        let target = helper::dsc_demo::lg_target();
+        assert!(target.validate().is_ok());
+        assert_eq!(Score::from(10.9), target.get_max_ring());
+    }
+
+    #[test]
+    fn test_validate_not_sorted() {
+        let mut target = helper::dsc_demo::lg_target();
+        target.rings.swap(2, 3);
+        match target.validate() {
+            Err(DisciplineError::RingsNotSorted(_)) => {},
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_no_rings() {
+        let mut target = helper::dsc_demo::lg_target();
+        target.rings.clear();
+        match target.validate() {
+            Err(DisciplineError::NoRings) => {},
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_default_max_ring() {
+        let mut target = helper::dsc_demo::lg_target();
+        target.max_ring = None;
+        target.rings[0].value = 5;
+        assert_eq!(Score::from(5.9), target.get_max_ring());
+    }
+}
@@ -0,0 +1,23 @@
+/// Rules to rank results with the same value. Results are always compared by their total first,
+/// the rules of a discipline are only applied to equal totals, in the given order, until one of
+/// them decides the ranking.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type")]
+pub enum TieBreak {
+    /// Sum of the counted shots, according to the count mode. It is always compared first, so
+    /// it does not have to be listed.
+    Total,
+    /// Number of inner tens, more is better
+    InnerTens,
+    /// Sums of the series, compared from the last series backwards
+    LastSeries,
+    /// Best (lowest) teiler of all shots
+    BestTeiler,
+}
+
+impl TieBreak {
+    /// Rules used, if the discipline defines no rules
+    pub fn default_rules() -> Vec<TieBreak> {
+        vec![TieBreak::Total]
+    }
+}
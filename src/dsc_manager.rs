@@ -3,15 +3,20 @@ use std::thread;
 use std::time::Duration;
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
+use std::collections::BTreeMap;
 
-use session::{Session, Update as UpdateSession, PartType, ActivePart, AddShotRaw};
+use session::{Session, Update as UpdateSession, PartType, ActivePart, AddShotRaw, rank_finals,
+    verify_chain, rank_teams, TeamEvent, LeagueMatchResult,
+    User, Club, Team, Equipment, SightScope, Score};
 use discipline::*;
 use device_api;
 use device_api::api::{API, Action, DeviceCommand};
 use config::{Config, DatabaseConfig};
 use web::{SendType, Log};
-use print::print;
+use print::{print, print_league_match};
 use database::handler::{DBHandler, DBHandlerNone, DBHandlerFileSystem};
+use database::filter::SessionFilter;
+use registry::{Registry, Shooter};
 
 pub type DSCManagerMutex = Arc<Mutex<DSCManager>>;
 pub type DSCManagerThread = thread::JoinHandle<()>;
@@ -50,6 +55,16 @@ pub struct DSCManager {
 
     shot_provider_state: ShotProviderState,
     pub config: Config,
+
+    // Registry of all known shooters, stored by the db handler
+    registry: Registry,
+
+    // Team event with live standings, sent after every change of the session
+    team_event_id: Option<String>,
+
+    // Stored sessions of the team event, read once when the standings are requested, so they are
+    // not read again after every shot
+    team_event_sessions: Vec<Session>,
 }
 
 impl DSCManager {
@@ -61,11 +76,23 @@ impl DSCManager {
         };
         // let db_handler = Box::new(DBHandlerNone::new());
 
-        // TODO REMOVE and just init session in one method
-        let discipline = config.default_discipline.clone();
-        // let session_id = db_handler.new_session_id(config.line.id);
-        // Dummy session, will change
-        let session = Session::new("0".to_string(), config.line.clone(), discipline);
+        // Resume the last session of this line, if it was not finished (e.g. after a crash)
+        let session = match db_handler.get_unfinished_session(config.line.id) {
+            Some(mut session) => {
+                println!("Resume session {:?}", session.id);
+                session.resume();
+                session
+            },
+            None => {
+                // TODO REMOVE and just init session in one method
+                let discipline = config.default_discipline.clone();
+                // let session_id = db_handler.new_session_id(config.line.id);
+                // Dummy session, will change
+                Session::new("0".to_string(), config.line.clone(), discipline)
+            },
+        };
+
+        let registry = db_handler.get_registry();
 
         let (get_from_device_tx, get_from_device_rx) = mpsc::channel::<Action>();
         let manager = DSCManager {
@@ -75,6 +102,9 @@ impl DSCManager {
             get_from_device_tx, get_from_device_rx,
             shot_provider_state: ShotProviderState::NotRunning,
             config,
+            registry,
+            team_event_id: None,
+            team_event_sessions: vec![],
         };
 
         let manager_mutex = Arc::new(Mutex::new(manager));
@@ -88,10 +118,15 @@ impl DSCManager {
     ///
     /// manager_mutex:  DSCMangerMutex, will be locked befor every access in the run loop
     pub fn start(manager: DSCManagerMutex) -> DSCManagerThread {
-        // Start default discipline
+        // Start default discipline, or continue with the resumed session
         if let Ok(mut manager) = manager.lock() {
-            let discipline = manager.session.discipline.clone();
-            manager.set_disciplin(discipline)
+            if manager.session.resumed {
+                manager.continue_session();
+            }
+            else {
+                let discipline = manager.session.discipline.clone();
+                manager.set_disciplin(discipline)
+            }
         }
 
         // Start and return main manager worker thread.
@@ -99,7 +134,11 @@ impl DSCManager {
         // update to the observer (over the on_update_tx channel).
         return thread::spawn(move || {
             loop {
-                manager.lock().unwrap().check_device_channel();
+                {
+                    let mut manager = manager.lock().unwrap();
+                    manager.check_device_channel();
+                    manager.check_timers();
+                }
                 thread::sleep(Duration::from_millis(100));
             }
         });
@@ -107,8 +146,197 @@ impl DSCManager {
 
 
 
-    pub fn get_stored_sessions(&self, since: SystemTime) -> Vec<Session> {
-        return self.db_handler.get_stored_sessions(since);
+    /// Send all stored sessions matching the given filter to the client
+    pub fn send_stored_sessions(&mut self, filter: SessionFilter) {
+        let sessions = self.db_handler.get_stored_sessions(&filter);
+        self.send_message_to_observer(SendType::StoredSessions { sessions });
+    }
+
+    /// Rank the finals of the current session and the stored sessions with the given ids, and
+    /// send the ranking to the client.
+    ///
+    /// session_ids:    ids of the sessions of the other lines
+    pub fn send_final_ranking(&mut self, session_ids: Vec<String>) {
+        let mut sessions = vec![self.session.clone()];
+        for session_id in session_ids {
+            if session_id == self.session.id {
+                continue;
+            }
+            match self.db_handler.get_session(&session_id) {
+                Some(session) => sessions.push(session),
+                None => println!("Session not found: {}", session_id),
+            }
+        }
+        let ranking = rank_finals(&sessions);
+        self.send_message_to_observer(SendType::FinalRanking { ranking });
+    }
+
+    /// Send the timing analysis of the current session to the client
+    pub fn send_timing_analysis(&mut self) {
+        let analysis = self.session.get_timing_analysis(SystemTime::now());
+        self.send_message_to_observer(SendType::TimingAnalysis { analysis });
+    }
+
+    /// Sign off the current session, it can not be changed afterwards. The hash of the session is
+    /// chained to the last finalized session of this line, the hash of the last one is stored.
+    ///
+    /// signed_off_by:  name of the person who signed off the result
+    pub fn finalize_session(&mut self, signed_off_by: String) {
+        let previous_hash = self.db_handler.get_last_hash(self.config.line.id);
+        if self.session.finalize(signed_off_by, previous_hash) {
+            self.update_sessions();
+            if let Some(ref finalization) = self.session.finalization {
+                self.db_handler.update_last_hash(self.config.line.id, &finalization.hash);
+            }
+            let message = format!("Session {} finalized", self.session.id);
+            self.send_message_to_observer(Log::new(message));
+        }
+        else {
+            self.send_message_to_observer(Log::new("Session is already finalized".to_string()));
+        }
+    }
+
+    /// Verify all finalized sessions of this line and send the result to the client. The chain
+    /// has to end with the stored hash of the last finalized session, otherwise the last sessions
+    /// are missing.
+    pub fn verify_finalized_sessions(&mut self) {
+        let sessions = self.db_handler.get_finalized_sessions(self.config.line.id);
+        let last_hash = sessions.last()
+            .and_then(|session| session.finalization.as_ref())
+            .map(|finalization| finalization.hash.clone());
+        let (valid, message) = match verify_chain(&sessions) {
+            Err(err) => (false, format!("{}", err)),
+            Ok(()) if last_hash != self.db_handler.get_last_hash(self.config.line.id) =>
+                (false, "The last finalized session is missing".to_string()),
+            Ok(()) => (true, format!("{} finalized sessions verified", sessions.len())),
+        };
+        self.send_message_to_observer(SendType::Verification { valid, message });
+    }
+
+    /// Send the results of the active part split by the runs of the moving target to the client
+    pub fn send_run_analysis(&mut self) {
+        let analysis = self.session.get_run_analysis();
+        self.send_message_to_observer(SendType::RunAnalysis { analysis });
+    }
+
+    /// Send the statistics of each face of the lane to the client
+    pub fn send_face_statistics(&mut self) {
+        let statistics = self.session.get_face_statistics();
+        self.send_message_to_observer(SendType::FaceStatistics { statistics });
+    }
+
+    /// Send the ranking of the lots of the current lot test to the client
+    pub fn send_lot_ranking(&mut self) {
+        match self.session.get_lot_ranking() {
+            Some(ranking) => self.send_message_to_observer(SendType::LotRanking { ranking }),
+            None => self.send_message_to_observer(Log::new("Session has no lot test".to_string())),
+        }
+    }
+
+    /// Send a sight correction for the current session to the client. We use the sight of the
+    /// shooter, if the shooter is in the registry and has one, otherwise the sight of the
+    /// discipline.
+    ///
+    /// scope:  shots to use for the mean point of impact
+    pub fn send_sight_correction(&mut self, scope: SightScope) {
+        let shooter_sight = self.registry.get(&self.session.info.user.id)
+            .and_then(|shooter| shooter.sight);
+        let sight = match shooter_sight.or(self.session.discipline.sight) {
+            Some(sight) => sight,
+            None => {
+                let message = "No sight configured for this shooter or discipline".to_string();
+                return self.send_message_to_observer(Log::new(message));
+            },
+        };
+        match self.session.get_sight_correction(scope, &sight) {
+            Some(correction) => self.send_message_to_observer(SendType::SightCorrection { correction }),
+            None => self.send_message_to_observer(Log::new("No shots for a sight correction".to_string())),
+        }
+    }
+
+    /// Send the standings of the team event with the given id to the client. The results of the
+    /// members are taken from the stored sessions of all lines and the current session. The
+    /// standings are sent again after every change of the current session, the stored sessions
+    /// of the other lines are only read again when the standings are requested again.
+    ///
+    /// event_id:   id of the team event in the config
+    pub fn send_team_standings(&mut self, event_id: String) {
+        self.team_event_id = Some(event_id);
+        self.load_team_event_sessions();
+        self.update_team_standings();
+    }
+
+    /// Send the result of the league match with the given id to the client
+    ///
+    /// match_id:   id of the league match in the config
+    pub fn send_league_match(&mut self, match_id: &str) {
+        if let Some(result) = self.get_league_match_result(match_id) {
+            self.send_message_to_observer(SendType::LeagueMatch { result });
+        }
+    }
+
+    /// Export the report of the league match with the given id
+    ///
+    /// match_id:   id of the league match in the config
+    pub fn print_league_match(&mut self, match_id: &str) {
+        if let Some(result) = self.get_league_match_result(match_id) {
+            if let Err(err) = print_league_match(&result) {
+                self.send_message_to_observer(Log::new(format!("{}", err)));
+            }
+        }
+    }
+
+    /// Add a new shooter to the registry and send all shooters to the client
+    pub fn create_shooter(&mut self, shooter: Shooter) {
+        // Clubs and teams of the shooters have to be in the roster
+        let shooter = match self.config.roster.validate_shooter(shooter) {
+            Ok(shooter) => shooter,
+            Err(err) => return self.send_message_to_observer(Log::new(format!("{}", err))),
+        };
+        match self.registry.create(shooter) {
+            Ok(_) => self.update_registry(),
+            Err(err) => self.send_message_to_observer(Log::new(format!("{}", err))),
+        }
+    }
+
+    /// Update a shooter of the registry and send all shooters to the client
+    pub fn update_shooter(&mut self, shooter: Shooter) {
+        // Clubs and teams of the shooters have to be in the roster
+        let shooter = match self.config.roster.validate_shooter(shooter) {
+            Ok(shooter) => shooter,
+            Err(err) => return self.send_message_to_observer(Log::new(format!("{}", err))),
+        };
+        match self.registry.update(shooter) {
+            Ok(_) => self.update_registry(),
+            Err(err) => self.send_message_to_observer(Log::new(format!("{}", err))),
+        }
+    }
+
+    /// Search the registry and send the matching shooters to the client
+    ///
+    /// query:  words to search for in name, id, license number, club and team
+    pub fn search_shooters(&mut self, query: &str) {
+        let shooters = self.registry.search(query);
+        self.send_message_to_observer(SendType::Shooters { shooters });
+    }
+
+    /// Assign the shooter with the given id from the registry to the current session
+    pub fn select_shooter(&mut self, shooter_id: &str) {
+        let shooter = self.registry.get(shooter_id).cloned();
+        match shooter {
+            Some(shooter) => self.set_shooter(shooter),
+            None => {
+                let message = format!("Shooter {} not found", shooter_id);
+                self.send_message_to_observer(Log::new(message));
+            },
+        }
+    }
+
+    /// Store the registry and send all shooters to the client
+    fn update_registry(&mut self) {
+        self.db_handler.update_registry(&self.registry);
+        let shooters = self.registry.search("");
+        self.send_message_to_observer(SendType::Shooters { shooters });
     }
 
     /// Send current session to the client
@@ -117,7 +345,102 @@ impl DSCManager {
         self.db_handler.update_sesssion(&self.session);
 
         let session = self.session.clone();
-        self.send_message_to_observer(SendType::Session { session })
+        self.send_message_to_observer(SendType::Session { session });
+        self.update_team_standings();
+    }
+
+    /// Inform the client, if the current session is finalized and a change is rejected
+    ///
+    /// change:     description of the rejected change
+    /// return:     true if the session is finalized
+    fn reject_if_finalized(&mut self, change: &str) -> bool {
+        if !self.session.is_finalized() {
+            return false;
+        }
+        let message = format!("Session {} is finalized, {} rejected", self.session.id, change);
+        self.send_message_to_observer(Log::new(message));
+        true
+    }
+
+    /// Return the selected team event, the selection is removed if the event is not found
+    fn get_team_event(&mut self) -> Option<TeamEvent> {
+        let event_id = match self.team_event_id {
+            Some(ref event_id) => event_id.clone(),
+            None => return None,
+        };
+        match self.config.team_events.iter().find(|event| event.id == event_id) {
+            Some(event) => Some(event.clone()),
+            None => {
+                self.team_event_id = None;
+                let message = format!("Team event {} not found", event_id);
+                self.send_message_to_observer(Log::new(message));
+                None
+            },
+        }
+    }
+
+    /// Read the stored sessions of the selected team event into the cache
+    fn load_team_event_sessions(&mut self) {
+        self.team_event_sessions = match self.get_team_event() {
+            Some(event) => {
+                let filter = SessionFilter { tags: event.tags, ..SessionFilter::default() };
+                self.db_handler.get_stored_sessions(&filter)
+            },
+            None => vec![],
+        };
+    }
+
+    /// Send the live standings of the selected team event, only the current session is updated,
+    /// the other sessions are taken from the cache
+    fn update_team_standings(&mut self) {
+        let event = match self.get_team_event() {
+            Some(event) => event,
+            None => return,
+        };
+        let sessions = self.with_current_session(&self.team_event_sessions, &event.tags);
+        let standings = rank_teams(&event, &sessions);
+        self.send_message_to_observer(SendType::TeamStandings { event_id: event.id, standings });
+    }
+
+    /// Calculate the result of the league match with the given id, an error is sent to the client
+    /// if the match is not found.
+    fn get_league_match_result(&mut self, match_id: &str) -> Option<LeagueMatchResult> {
+        let league_match = match self.config.league_matches.iter().find(|league_match| league_match.id == match_id) {
+            Some(league_match) => league_match.clone(),
+            None => {
+                let message = format!("League match {} not found", match_id);
+                self.send_message_to_observer(Log::new(message));
+                return None;
+            },
+        };
+        let sessions = self.get_sessions_of_all_lines(&league_match.tags);
+        Some(league_match.get_result(&sessions))
+    }
+
+    /// Return the stored sessions of all lines with the given tags, the current session replaces
+    /// its stored version.
+    ///
+    /// tags:   tags the sessions have to have
+    fn get_sessions_of_all_lines(&self, tags: &BTreeMap<String, String>) -> Vec<Session> {
+        let filter = SessionFilter { tags: tags.clone(), ..SessionFilter::default() };
+        self.with_current_session(&self.db_handler.get_stored_sessions(&filter), tags)
+    }
+
+    /// Return the given stored sessions together with the current session, if it has the given
+    /// tags. The current session replaces its stored version.
+    ///
+    /// stored: stored sessions
+    /// tags:   tags the current session has to have
+    fn with_current_session(&self, stored: &[Session], tags: &BTreeMap<String, String>) -> Vec<Session> {
+        let filter = SessionFilter { tags: tags.clone(), ..SessionFilter::default() };
+        let mut sessions: Vec<Session> = stored.iter()
+            .filter(|session| session.id != self.session.id)
+            .cloned()
+            .collect();
+        if filter.matches(&self.session) {
+            sessions.push(self.session.clone());
+        }
+        sessions
     }
 
     /// Send given message to on_update channel (e.g. to websocket)
@@ -136,10 +459,24 @@ impl DSCManager {
         if let Ok(message) = self.get_from_device_rx.try_recv() {
             match message {
                 Action::NewShot(shot_raw) => {
+                    // The shot is lost, the operator has to start a new session for it
+                    if self.reject_if_finalized(&format!("shot ({}, {})", shot_raw.x, shot_raw.y)) {
+                        return;
+                    }
                     // TODO add return type to add_shot_raw to show message in frontend if we need
                     // to send ad message (e.g. time is up)
+                    let game_was_running = self.session.game.as_ref().map_or(false, |game| game.is_running());
                     self.session.add_shot_raw(shot_raw);
                     self.update_sessions();
+
+                    // Show the result, if the game ended with this shot
+                    let game_result = match self.session.game {
+                        Some(ref game) if game_was_running && !game.is_running() => Some(game.text.clone()),
+                        _ => None,
+                    };
+                    if let Some(text) = game_result {
+                        self.send_message_to_observer(Log::new(text));
+                    }
                 },
                 Action::Error(err) => {
                     println!("Error from device_api {:?}", err);
@@ -153,6 +490,26 @@ impl DSCManager {
 
 
 
+    /// Continue with the current session (e.g. a resumed session after a restart), start the
+    /// shot provider of its discipline and inform the observer.
+    fn continue_session(&mut self) {
+        let discipline = self.session.discipline.clone();
+        self.start_shot_provider(discipline);
+        self.update_sessions();
+        let message = format!("Resumed session {}", self.session.id);
+        self.send_message_to_observer(Log::new(message));
+    }
+
+    /// Update the timers of the session (e.g. the sighting period of a final), and send an update
+    /// to the observer if the session changed.
+    fn check_timers(&mut self) {
+        if self.session.update_timers() {
+            self.update_sessions();
+        }
+    }
+
+
+
     /// Start given shot provider, if we still have a running one, we stop it.
     ///
     /// discipline:      new discipline
@@ -238,6 +595,11 @@ pub trait UpdateManager {
     /// discipline_id:   id of the discipline (the filename from the config, without suffix)
     fn set_disciplin_by_name(&mut self, discipline_id: &str);
 
+    /// Start a training game by name
+    /// Will search the config for a game with given name, and start it in the current session.
+    ///
+    /// game_id:    id of the game (the filename from the config, without suffix)
+    fn start_game_by_name(&mut self, game_id: &str);
 
     fn print_session(&self);
 }
@@ -248,6 +610,17 @@ impl UpdateManager for DSCManager {
         println!("Set discipline {:?}", discipline.id);
         self.start_shot_provider(discipline.clone());
 
+        // Finish the old session, so it will not be resumed after a restart
+        if !self.session.events.is_empty() {
+            self.session.finish();
+            self.db_handler.update_sesssion(&self.session);
+        }
+
+        // The finished session is stored now, so it is read into the team event cache
+        if self.team_event_id.is_some() {
+            self.load_team_event_sessions();
+        }
+
         let session_id = self.db_handler.new_session_id(self.config.line.id);
         self.session = Session::new(session_id, self.config.line.clone(), discipline);
         // TODO init session in db, and set session id
@@ -263,6 +636,16 @@ impl UpdateManager for DSCManager {
         }
     }
 
+    fn start_game_by_name(&mut self, game_id: &str) {
+        match self.config.get_game(game_id) {
+            Some(game) => self.start_game(game),
+            None => {
+                println!("Game to start not found: {}", game_id);
+                self.send_message_to_observer(Log::new(format!("Game {} not found", game_id)));
+            },
+        }
+    }
+
     fn print_session(&self) {
         let print_error = print(&self.session);
         println!("{:?}", print_error);
@@ -276,6 +659,9 @@ impl UpdateSession for DSCManager {
 
     fn new_target(&mut self) {
         println!("new_target");
+        if self.reject_if_finalized("new target") {
+            return;
+        }
         self.session.new_target();
         self.update_sessions();
 
@@ -302,21 +688,113 @@ impl UpdateSession for DSCManager {
 
 
 
-    // fn set_user(&mut self, user: User) {
-    //     self.session.user = user;
-    //     self.update_sessions();
-    // }
-    // fn set_team(&mut self, team: Team) {
-    //     self.session.team = team;
-    //     self.update_sessions();
-    // }
-    // fn set_club(&mut self, club: Club) {
-    //     self.session.club = club;
-    //     self.update_sessions();
-    // }
+    fn set_user(&mut self, user: User) {
+        println!("set_user {:?}", user);
+        if self.reject_if_finalized("set user") {
+            return;
+        }
+        match self.config.roster.validate_user(user, &self.registry) {
+            Ok(user) => {
+                self.session.set_user(user);
+                self.update_sessions();
+            },
+            Err(err) => self.send_message_to_observer(Log::new(format!("{}", err))),
+        }
+    }
+    fn set_team(&mut self, team: Team) {
+        println!("set_team {:?}", team);
+        if self.reject_if_finalized("set team") {
+            return;
+        }
+        match self.config.roster.validate_team(team) {
+            Ok(team) => {
+                self.session.set_team(team);
+                self.update_sessions();
+            },
+            Err(err) => self.send_message_to_observer(Log::new(format!("{}", err))),
+        }
+    }
+    fn set_club(&mut self, club: Club) {
+        println!("set_club {:?}", club);
+        if self.reject_if_finalized("set club") {
+            return;
+        }
+        match self.config.roster.validate_club(club) {
+            Ok(club) => {
+                self.session.set_club(club);
+                self.update_sessions();
+            },
+            Err(err) => self.send_message_to_observer(Log::new(format!("{}", err))),
+        }
+    }
+
+    fn set_shooter(&mut self, shooter: Shooter) {
+        println!("set_shooter {:?}", shooter.id);
+        if self.reject_if_finalized("set shooter") {
+            return;
+        }
+        let roster = &self.config.roster;
+        let validated = roster.validate_user(shooter.get_user(), &self.registry)
+            .and_then(|_| roster.validate_shooter(shooter));
+        match validated {
+            Ok(shooter) => {
+                self.session.set_shooter(shooter);
+                self.update_sessions();
+            },
+            Err(err) => self.send_message_to_observer(Log::new(format!("{}", err))),
+        }
+    }
+
+    fn add_shooter(&mut self, user: User, club: Club, team: Team) {
+        println!("add_shooter {:?}", user);
+        if self.reject_if_finalized("add shooter") {
+            return;
+        }
+        let roster = &self.config.roster;
+        let validated = roster.validate_user(user, &self.registry)
+            .and_then(|user| roster.validate_club(club).map(|club| (user, club)))
+            .and_then(|(user, club)| roster.validate_team(team).map(|team| (user, club, team)));
+        match validated {
+            Ok((user, club, team)) => {
+                self.session.add_shooter(user, club, team);
+                self.update_sessions();
+            },
+            Err(err) => self.send_message_to_observer(Log::new(format!("{}", err))),
+        }
+    }
+
+    fn switch_shooter(&mut self, shooter: usize) {
+        println!("switch_shooter {:?}", shooter);
+        if self.reject_if_finalized("switch shooter") {
+            return;
+        }
+        self.session.switch_shooter(shooter);
+        self.update_sessions();
+    }
+
+    fn set_tag(&mut self, key: String, value: Option<String>) {
+        println!("set_tag {:?} {:?}", key, value);
+        if self.reject_if_finalized("set tag") {
+            return;
+        }
+        self.session.set_tag(key, value);
+        self.update_sessions();
+    }
+
+    fn set_equipment(&mut self, equipment: Equipment) {
+        println!("set_equipment {:?}", equipment);
+        if self.reject_if_finalized("set equipment") {
+            return;
+        }
+        self.session.set_equipment(equipment);
+        self.update_sessions();
+    }
 
     fn set_part(&mut self, part_type: PartType, force: bool) {
         println!("set_part {:?}", part_type);
+        if self.reject_if_finalized("set part") {
+            return;
+        }
         self.session.set_part(part_type, force);
         self.update_sessions();
 
@@ -329,7 +807,104 @@ impl UpdateSession for DSCManager {
     }
     fn set_active_part(&mut self, index: ActivePart, force: bool) {
         println!("set_active_part {:?}", index);
+        if self.reject_if_finalized("set active part") {
+            return;
+        }
         self.session.set_active_part(index, force);
         self.update_sessions();
     }
+    fn start_final_stage(&mut self) {
+        println!("start_final_stage");
+        if self.reject_if_finalized("start final stage") {
+            return;
+        }
+        self.session.start_final_stage();
+        self.update_sessions();
+    }
+    fn invalidate_shot(&mut self, shot_id: usize) {
+        println!("invalidate_shot {:?}", shot_id);
+        if self.reject_if_finalized("invalidate shot") {
+            return;
+        }
+        self.session.invalidate_shot(shot_id);
+        self.update_sessions();
+    }
+    fn move_shots(&mut self, from_part: ActivePart, first_shot: i32, last_shot: i32, to_part: ActivePart) {
+        println!("move_shots {:?}-{:?} from {:?} to {:?}", first_shot, last_shot, from_part, to_part);
+        if self.reject_if_finalized("move shots") {
+            return;
+        }
+        self.session.move_shots(from_part, first_shot, last_shot, to_part);
+        self.update_sessions();
+    }
+    fn add_penalty(&mut self, part: ActivePart, amount: Score, reason: String, officer: String) {
+        println!("add_penalty {:?} to part {:?} by {:?}: {:?}", amount, part, officer, reason);
+        if self.reject_if_finalized("add penalty") {
+            return;
+        }
+        self.session.add_penalty(part, amount, reason, officer);
+        self.update_sessions();
+    }
+    fn select_run(&mut self, run: Option<Run>) {
+        println!("select_run {:?}", run);
+        if self.reject_if_finalized("select run") {
+            return;
+        }
+        self.session.select_run(run);
+        self.update_sessions();
+    }
+    fn set_shot_run(&mut self, shot_id: usize, run: Run) {
+        println!("set_shot_run {:?} {:?}", shot_id, run);
+        if self.reject_if_finalized("set shot run") {
+            return;
+        }
+        self.session.set_shot_run(shot_id, run);
+        self.update_sessions();
+    }
+    fn pause(&mut self, reason: Option<String>) {
+        println!("pause {:?}", reason);
+        if self.reject_if_finalized("pause") {
+            return;
+        }
+        self.session.pause(reason);
+        self.update_sessions();
+        if self.session.is_paused() {
+            self.send_message_to_observer(Log::new("Time limits paused".to_string()));
+        }
+    }
+    fn resume_timer(&mut self) {
+        println!("resume_timer");
+        if self.reject_if_finalized("resume timer") {
+            return;
+        }
+        self.session.resume_timer();
+        self.update_sessions();
+        if !self.session.is_paused() {
+            self.send_message_to_observer(Log::new("Time limits resumed".to_string()));
+        }
+    }
+    fn start_game(&mut self, game: Game) {
+        println!("start_game {:?}", game.id);
+        if self.reject_if_finalized("start game") {
+            return;
+        }
+        self.session.start_game(game);
+        self.update_sessions();
+    }
+    fn stop_game(&mut self) {
+        println!("stop_game");
+        if self.reject_if_finalized("stop game") {
+            return;
+        }
+        self.session.stop_game();
+        self.update_sessions();
+    }
+    fn undo(&mut self) {
+        println!("undo");
+        if self.reject_if_finalized("undo") {
+            return;
+        }
+        self.session.undo();
+        self.update_sessions();
+    }
 }
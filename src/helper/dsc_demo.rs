@@ -1,5 +1,6 @@
 use discipline::*;
 use session::counter::CountMode;
+use session::Score;
 
 
 
@@ -115,6 +116,8 @@ pub fn lg_discipline() -> Discipline {
             inner_ten: 200,
             trial_corner_color: WebColor { hex: String::from("#0f0"), alpha: 0.7 },
             bullet_diameter: 4.5,
+            max_ring: Some(Score::from(10.9)),
+            teiler_factor: None,
         },
         parts: vec![
             DisciplinePart {
@@ -130,8 +133,14 @@ pub fn lg_discipline() -> Discipline {
                 time: Time::None,
                 average: PartAverage::Average { number_of_shots: 40 },
                 exit_type: PartExitType::Always,
+                mode: PartMode::Default,
+                runs: vec![],
+                one_hit_per_face: false,
             },
         ],
+        tie_break: TieBreak::default_rules(),
+        sight: None,
+        faces: vec![],
     }
 }
 
@@ -238,6 +247,8 @@ pub fn lg_target() -> Target {
         inner_ten: 200,
         trial_corner_color: WebColor { hex: String::from("000000"), alpha: 1.0 },
         bullet_diameter: 4.5,
+        max_ring: Some(Score::from(10.9)),
+        teiler_factor: None,
     }
 }
 
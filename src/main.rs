@@ -26,6 +26,9 @@ extern crate clap;
 
 extern crate dotenv;
 
+// hashes of finalized sessions
+extern crate sha2;
+
 
 mod config;
 mod discipline;
@@ -36,13 +39,18 @@ mod device_api;
 mod web;
 mod database;
 mod print;
+mod registry;
+mod offline;
 
 use std::thread;
 
 use std::path::Path;
-use clap::{Arg, App};
+use clap::{Arg, App, AppSettings, SubCommand, ArgMatches};
 
-use config::Config;
+use config::{Config, DatabaseConfig};
+use database::handler::DBHandlerFileSystem;
+use database::filter::SessionFilter;
+use database::rescore::rescore_stored_sessions;
 use dsc_manager::DSCManager;
 use web::{Config as SocketConfig, socket};
 
@@ -51,9 +59,18 @@ use web::{Config as SocketConfig, socket};
 
 
 // Main Entry Point
-// 1. Parse Config, crash if not valid
-// 2. Start DSC
+// 1. Run offline subcommand, if given
+// 2. Parse Config, crash if not valid
+// 3. Start DSC
 fn main() {
+    let target_arg = Arg::with_name("target")
+        .short("t")
+        .long("target")
+        .value_name("TARGET")
+        .help("Name of a target in the modes dir (e.g. lg), or path to a target json file")
+        .required(true)
+        .takes_value(true);
+
     let matches = App::new("DSC")
                         .version("1.0")
                         .author("Jannik Lorenz <mail@janniklorenz.de>")
@@ -72,13 +89,45 @@ fn main() {
                             .help("Path to modes dir, if not present ./config/modes/ will be used")
                             .required(false)
                             .takes_value(true))
+                        .subcommand(SubCommand::with_name("score")
+                            .about("Print ring, teiler, angle and inner ten of a shot")
+                            .setting(AppSettings::AllowNegativeNumbers)
+                            .arg(target_arg.clone())
+                            .arg(Arg::with_name("x")
+                                .help("x coordinate in 1/1000 mm")
+                                .required(true))
+                            .arg(Arg::with_name("y")
+                                .help("y coordinate in 1/1000 mm")
+                                .required(true)))
+                        .subcommand(SubCommand::with_name("rescore")
+                            .about("Score a stored session again with the given target, the file is not changed")
+                            .arg(target_arg.clone())
+                            .arg(Arg::with_name("session")
+                                .value_name("FILE")
+                                .help("Path to a .dscSession file")
+                                .required(true)))
+                        .subcommand(SubCommand::with_name("rescore-all")
+                            .about("Score all stored sessions of the target again and save the changed sessions")
+                            .arg(target_arg)
+                            .arg(Arg::with_name("dry-run")
+                                .long("dry-run")
+                                .help("Only print the changes, without saving the sessions")))
                           .get_matches();
 
     let config_dir = matches.value_of("config").unwrap_or("./config/config.json");
     let modes_dir = matches.value_of("modes").unwrap_or("./config/modes/");
 
+    match matches.subcommand() {
+        ("score", Some(sub_matches)) => return run_score(Path::new(modes_dir), sub_matches),
+        ("rescore", Some(sub_matches)) => return run_rescore(Path::new(modes_dir), sub_matches),
+        _ => {},
+    }
+
     match Config::new(Path::new(config_dir), Path::new(modes_dir)) {
-        Ok(config) => start_dsc(config),
+        Ok(config) => match matches.subcommand() {
+            ("rescore-all", Some(sub_matches)) => run_rescore_all(config, Path::new(modes_dir), sub_matches),
+            _ => start_dsc(config),
+        },
         Err(err) => println!("Error in config: {}", err),
     }
 }
@@ -100,3 +149,54 @@ fn start_dsc(config: Config) {
     // Run until manager (and socket?! TODO) finishes
     manager_thread.join().unwrap();
 }
+
+// Score a single shot, without device and websocket
+fn run_score(modes_dir: &Path, matches: &ArgMatches) {
+    let x = matches.value_of("x").unwrap().parse::<i32>();
+    let y = matches.value_of("y").unwrap().parse::<i32>();
+    let (x, y) = match (x, y) {
+        (Ok(x), Ok(y)) => (x, y),
+        _ => return println!("Error: coordinates have to be integers in 1/1000 mm"),
+    };
+    match Config::load_target(modes_dir, matches.value_of("target").unwrap()) {
+        Ok(target) => println!("{}", offline::score(&target, x, y)),
+        Err(err) => println!("Error in target: {}", err),
+    }
+}
+
+// Score a stored session again and print the differences
+fn run_rescore(modes_dir: &Path, matches: &ArgMatches) {
+    let target = match Config::load_target(modes_dir, matches.value_of("target").unwrap()) {
+        Ok(target) => target,
+        Err(err) => return println!("Error in target: {}", err),
+    };
+    match offline::rescore_file(Path::new(matches.value_of("session").unwrap()), &target) {
+        Ok(ref report) if report.is_empty() => println!("Session {}: no changes", report.session_id),
+        Ok(report) => println!("{}", report),
+        Err(err) => println!("Error in session: {}", err),
+    }
+}
+
+// Score all stored sessions of a target again, and save the changed sessions
+fn run_rescore_all(config: Config, modes_dir: &Path, matches: &ArgMatches) {
+    let target = match Config::load_target(modes_dir, matches.value_of("target").unwrap()) {
+        Ok(target) => target,
+        Err(err) => return println!("Error in target: {}", err),
+    };
+    let db_handler = match config.database {
+        DatabaseConfig::FileSystem { path } => DBHandlerFileSystem::new(path),
+        DatabaseConfig::None => return println!("Error: no database configured"),
+    };
+    let write = !matches.is_present("dry-run");
+    let result = rescore_stored_sessions(&db_handler, &target, &SessionFilter::default(), write);
+    for report in &result.changed {
+        println!("{}", report);
+    }
+    for report in &result.needs_review {
+        println!("{}\n  Finalized, not saved, needs manual review", report);
+    }
+    println!("{} sessions changed{}", result.changed.len(), if write { "" } else { " (dry run, nothing saved)" });
+    if !result.needs_review.is_empty() {
+        println!("{} finalized sessions need manual review", result.needs_review.len());
+    }
+}
@@ -0,0 +1,67 @@
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+use serde_json;
+
+use config::error::Error as ConfigError;
+use discipline::Target;
+use session::{Session, Shot, CountMode, RescoreReport, rescore};
+
+
+
+// Score a single shot with the given target and return the result as text
+// target:  target to use
+// x, y:    coordinates of the shot in 1/1000 mm
+// return:  ring, teiler, angle and inner ten flag, one per line
+pub fn score(target: &Target, x: i32, y: i32) -> String {
+    let shot = Shot::from_cartesian_coordinates(x, y, target, &CountMode::Tenth);
+    format!(
+        "Ring:      {}\nTeiler:    {}\nAngle:     {:.1}\nInner ten: {}",
+        shot.ring, shot.teiler, shot.angle, if shot.is_inner_ten { "yes" } else { "no" }
+    )
+}
+
+// Read a stored session file and score it again with the given target. The file is not changed.
+// path:    path to the .dscSession file
+// target:  target to use, e.g. a corrected target definition
+// return:  differences between the stored and the new scores
+pub fn rescore_file(path: &Path, target: &Target) -> Result<RescoreReport, ConfigError> {
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    let session: Session = serde_json::from_str(&text)?;
+    let (_, report) = rescore(&session, target);
+    Ok(report)
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use offline::*;
+    use session::{Line, ShotRaw, AddShotRaw, Score};
+    use helper;
+
+    #[test]
+    fn test_score() {
+        let text = score(&helper::dsc_demo::lg_target(), 2501, 0);
+        assert_eq!("Ring:      9.9\nTeiler:    250.1\nAngle:     0.0\nInner ten: no", text);
+    }
+
+    #[test]
+    fn test_rescore_file() {
+        let mut session = Session::new("0".to_string(), Line::demo(), helper::dsc_demo::lg_discipline());
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        // The stored score differs from the score of the events, e.g. scored with an older target
+        session.parts[0].series[0].shots[0].ring = Score::from(10.4);
+
+        let path = env::temp_dir().join("dsc_test_rescore_file.dscSession");
+        File::create(&path).unwrap().write_all(serde_json::to_string(&session).unwrap().as_bytes()).unwrap();
+        let report = rescore_file(&path, &helper::dsc_demo::lg_target()).unwrap();
+        assert_eq!(1, report.shots.len());
+        assert_eq!(Score::from(10.4), report.shots[0].old_ring);
+        assert_eq!(Score::from(10.9), report.shots[0].new_ring);
+    }
+}
@@ -6,7 +6,7 @@ use tera::Error as TerraError;
 use tera::{Context, Tera};
 // use std::process::Command;
 
-use session::Session;
+use session::{Session, LotRank, LeagueMatchResult};
 
 
 // Use given template name to generate a tex file for given session and return it as a string
@@ -20,8 +20,30 @@ fn create_tex_session(template_name: &str, session: &Session) -> Result<String,
     return tera.render(&template_name, &context);
 }
 
+// Use the lot test template to generate a tex file with the comparison of the lots
+// session: session with a lot test part
+// ranking: ranking of the lots
+// return: rendered tex string
+fn create_tex_lot_test(session: &Session, ranking: &[LotRank]) -> Result<String, TerraError> {
+    let tera = Tera::new("templates/print/*")?;
+    let mut context = Context::new();
+    context.insert("session", session);
+    context.insert("ranking", ranking);
+    return tera.render("lot_test.tex", &context);
+}
+
+// Use the league match template to generate a tex file with the duels of a league match
+// result: result of the league match
+// return: rendered tex string
+fn create_tex_league_match(result: &LeagueMatchResult) -> Result<String, TerraError> {
+    let tera = Tera::new("templates/print/*")?;
+    let mut context = Context::new();
+    context.insert("result", result);
+    return tera.render("league_match.tex", &context);
+}
+
 
-// Print given session
+// Print given session, on a shared line only the active shooter is printed
 // 1. generate tex string
 // 2. save to file
 // 3. generate and save svg image for each series
@@ -32,12 +54,27 @@ pub fn print(session: &Session) -> Result<(), Error> {
     let mut file = File::create("templates/tmp/foo.tex")?;
     file.write_all(tex_string.as_bytes())?;
 
+    // Lot tests get an additional report with the comparison of the lots
+    if let Some(ranking) = session.get_lot_ranking() {
+        let tex_string = create_tex_lot_test(&session, &ranking)?;
+        let mut file = File::create("templates/tmp/lot_test.tex")?;
+        file.write_all(tex_string.as_bytes())?;
+    }
 
     // let _ = Command::new("sudo").arg("/sbin/shutdown");
 
     return Ok(());
 }
 
+// Export the report of a league match
+// result: result of the league match
+pub fn print_league_match(result: &LeagueMatchResult) -> Result<(), Error> {
+    let tex_string = create_tex_league_match(result)?;
+    let mut file = File::create("templates/tmp/league_match.tex")?;
+    file.write_all(tex_string.as_bytes())?;
+    return Ok(());
+}
+
 
 
 #[derive(Debug)]
@@ -85,3 +122,43 @@ impl From<IOError> for Error {
         Error::SaveError(err)
     }
 }
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use print::*;
+    use session::{Session, Line, ShotRaw, AddShotRaw, CountMode, User, Team, LeagueMatch};
+    use session::league_match::Pairing;
+    use helper;
+
+    #[test]
+    fn test_league_match_zero_result() {
+        let user = |id: &str| User { first_name: id.to_string(), last_name: "".to_string(), id: id.to_string() };
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].count_mode = CountMode::Integer;
+        let mut session = Session::new("a".to_string(), Line::demo(), discipline);
+        session.info.user = user("1");
+        session.add_shot_raw(ShotRaw { x: 50000, y: 0 });
+        let league_match = LeagueMatch {
+            id: "0".to_string(),
+            name: "Round 1".to_string(),
+            discipline: helper::dsc_demo::lg_discipline().id,
+            home: Team { name: "Home".to_string(), id: "1".to_string() },
+            away: Team { name: "Away".to_string(), id: "2".to_string() },
+            pairings: vec![Pairing { position: 1, home: user("1"), away: user("2") }],
+            tags: BTreeMap::new(),
+        };
+        let result = league_match.get_result(&[session]);
+        assert_eq!(Some(0.0), result.duels[0].home_result.map(|result| result.to_f64()));
+
+        // A result of 0 is printed, only a missing result is printed as -
+        let tex = create_tex_league_match(&result).unwrap();
+        let line = tex.lines().find(|line| line.trim_start().starts_with("1.")).unwrap();
+        assert!(line.contains("& 0"));
+        assert!(line.contains("& - &"));
+    }
+}
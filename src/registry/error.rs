@@ -0,0 +1,30 @@
+use std::error::Error as StdError;
+use std::fmt;
+
+
+
+#[derive(Debug)]
+pub enum Error {
+    ShooterNotFound(String),
+    DuplicateShooter(String),
+    MissingName,
+}
+
+impl StdError for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::ShooterNotFound(_) => "Shooter not found",
+            Error::DuplicateShooter(_) => "Duplicate shooter",
+            Error::MissingName => "Missing name",
+        }
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::ShooterNotFound(ref id) => write!(f, "Shooter {} not found", id),
+            Error::DuplicateShooter(ref id) => write!(f, "Shooter {} already exists", id),
+            Error::MissingName => write!(f, "Shooter needs a first or last name"),
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod error;
+pub mod registry;
+pub mod shooter;
+
+pub use self::registry::Registry;
+pub use self::shooter::Shooter;
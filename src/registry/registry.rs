@@ -0,0 +1,136 @@
+use super::Shooter;
+use super::error::Error;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+
+
+/// Registry of all known shooters, persisted by the database handler.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Registry {
+    pub shooters: Vec<Shooter>,
+}
+
+impl Registry {
+    /// Add a new shooter to the registry, if the shooter has no id, we generate one
+    /// shooter:    shooter to add
+    /// return:     the added shooter, or an error if the id is already in use
+    pub fn create(&mut self, mut shooter: Shooter) -> Result<Shooter> {
+        if shooter.first_name.is_empty() && shooter.last_name.is_empty() {
+            return Err(Error::MissingName);
+        }
+        if shooter.id.is_empty() {
+            shooter.id = self.next_id();
+        }
+        else if self.get(&shooter.id).is_some() {
+            return Err(Error::DuplicateShooter(shooter.id));
+        }
+        self.shooters.push(shooter.clone());
+        Ok(shooter)
+    }
+
+    /// Replace the shooter with the same id
+    /// shooter:    new data of the shooter
+    pub fn update(&mut self, shooter: Shooter) -> Result<Shooter> {
+        if shooter.first_name.is_empty() && shooter.last_name.is_empty() {
+            return Err(Error::MissingName);
+        }
+        match self.shooters.iter_mut().find(|known| known.id == shooter.id) {
+            Some(known) => {
+                *known = shooter.clone();
+                Ok(shooter)
+            },
+            None => Err(Error::ShooterNotFound(shooter.id)),
+        }
+    }
+
+    /// Return the shooter with the given id
+    pub fn get(&self, id: &str) -> Option<&Shooter> {
+        self.shooters.iter().find(|shooter| shooter.id == id)
+    }
+
+    /// Return all shooters matching the given query, sorted by name. An empty query matches all
+    /// shooters.
+    pub fn search(&self, query: &str) -> Vec<Shooter> {
+        let mut shooters: Vec<Shooter> = self.shooters.iter()
+            .filter(|shooter| shooter.matches(query))
+            .cloned()
+            .collect();
+        shooters.sort_by(|a, b| (&a.last_name, &a.first_name).cmp(&(&b.last_name, &b.first_name)));
+        shooters
+    }
+
+    /// Generate a new numeric id, which is not used yet
+    fn next_id(&self) -> String {
+        let max = self.shooters.iter()
+            .filter_map(|shooter| shooter.id.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("{}", max + 1)
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use registry::*;
+    use session::{Club, Team};
+
+    fn shooter(first_name: &str, last_name: &str, id: &str) -> Shooter {
+        Shooter {
+            id: id.to_string(),
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+            club: Club { name: "SV Musterdorf".to_string(), id: "1".to_string() },
+            team: Team::empty(),
+            age_class: "Herren".to_string(),
+            license_number: "0815".to_string(),
+            default_disciplines: vec!["lg".to_string()],
+            sight: None,
+        }
+    }
+
+    #[test]
+    fn test_create() {
+        let mut registry = Registry::default();
+        let created = registry.create(shooter("Max", "Mustermann", "")).unwrap();
+        assert_eq!("1", created.id);
+        let created = registry.create(shooter("Erika", "Musterfrau", "")).unwrap();
+        assert_eq!("2", created.id);
+        assert!(registry.create(shooter("Erika", "Musterfrau", "2")).is_err());
+        assert!(registry.create(shooter("", "", "")).is_err());
+        assert_eq!(2, registry.shooters.len());
+    }
+
+    #[test]
+    fn test_update() {
+        let mut registry = Registry::default();
+        registry.create(shooter("Max", "Mustermann", "")).unwrap();
+        let mut updated = shooter("Max", "Meier", "1");
+        updated.license_number = "4711".to_string();
+        registry.update(updated).unwrap();
+        assert_eq!("Meier", registry.get("1").unwrap().last_name);
+        assert_eq!("4711", registry.get("1").unwrap().license_number);
+        assert!(registry.update(shooter("Max", "Meier", "5")).is_err());
+    }
+
+    #[test]
+    fn test_search() {
+        let mut registry = Registry::default();
+        registry.create(shooter("Max", "Mustermann", "")).unwrap();
+        registry.create(shooter("Erika", "Musterfrau", "")).unwrap();
+        registry.create(shooter("Hans", "Meier", "")).unwrap();
+
+        let found = registry.search("");
+        assert_eq!(3, found.len());
+        assert_eq!("Meier", found[0].last_name);
+        assert_eq!("Musterfrau", found[1].last_name);
+        assert_eq!(1, registry.search("ERIKA").len());
+        assert_eq!(1, registry.search("max muster").len());
+        assert_eq!(3, registry.search("musterdorf").len());
+        assert_eq!(0, registry.search("schmidt").len());
+    }
+}
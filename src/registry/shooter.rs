@@ -0,0 +1,52 @@
+use session::{User, Club, Team};
+use discipline::Sight;
+
+
+
+/// Shooter stored in the registry, can be assigned to the sessions of all lines.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Shooter {
+    /// Unique id, generated by the registry if empty on creation
+    #[serde(default)]
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    #[serde(default = "Club::empty")]
+    pub club: Club,
+    #[serde(default = "Team::empty")]
+    pub team: Team,
+    #[serde(default)]
+    pub age_class: String,
+    #[serde(default)]
+    pub license_number: String,
+    /// Ids of the disciplines (filename from the config) usually shot
+    #[serde(default)]
+    pub default_disciplines: Vec<String>,
+    /// Sight of the shooter, used for sight corrections instead of the sight of the discipline
+    #[serde(default)]
+    pub sight: Option<Sight>,
+}
+
+impl Shooter {
+    /// Return the user info for a session
+    pub fn get_user(&self) -> User {
+        User {
+            first_name: self.first_name.clone(),
+            last_name: self.last_name.clone(),
+            id: self.id.clone(),
+        }
+    }
+
+    /// Check if the shooter matches the given search query. Each word of the query has to be
+    /// part (ignoring case) of the name, id, license number or club/ team name.
+    pub fn matches(&self, query: &str) -> bool {
+        let fields = [
+            &self.first_name, &self.last_name, &self.id, &self.license_number,
+            &self.club.name, &self.team.name,
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| field.to_lowercase()).collect();
+        query.to_lowercase().split_whitespace().all(|word| {
+            fields.iter().any(|field| field.contains(word))
+        })
+    }
+}
@@ -1,71 +1,151 @@
 
 
 
+use std::cmp::Ordering;
+
+use super::{Score, Shot};
+
+
+
 /// Wrapper struct to support multiple ways to calculate the ring value.
-/// Currenty we have 2 count modes, Integer and Tenth.
+/// Currenty we have 4 count modes, Integer, Tenth and the teiler modes Teiler and TeilerSum.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Counter {
-    pub value: f64,
+    pub value: Score,
     text: String,
+    /// Number of values added to the counter, missing in counters stored before it was added
+    #[serde(default)]
+    count: i32,
 }
 
 impl Counter {
     /// Create new counter with 0 as value.
     pub fn empty() -> Counter {
-        Counter::new(0_f64, &CountMode::Integer)
+        Counter::new(Score::zero(), &CountMode::Integer)
     }
 
     /// Create new counter with given value and count mode.
     /// value:          value to use
     /// count_mode:     count mode to apply on the value, will only be used once
     /// return:         counter
-    pub fn new(value: f64, count_mode: &CountMode) -> Counter {
+    pub fn new(value: Score, count_mode: &CountMode) -> Counter {
         Counter {
             value: count_mode.round(value),
             text: count_mode.to_string(value),
+            count: 0,
         }
     }
 
-    /// Add a value to the counter.
+    /// Add a value to the counter. In CountMode::Teiler we only keep the lowest value.
     /// other:          value to add
     /// count_mode:     will be used befor we add the value to self.value,
     ///                 and for the updated string
-    pub fn add(&mut self, other: f64, count_mode: &CountMode) {
-        self.value += count_mode.round(other);
+    pub fn add(&mut self, other: Score, count_mode: &CountMode) {
+        match *count_mode {
+            CountMode::Teiler => {
+                let other = count_mode.round(other);
+                if self.count == 0 || other < self.value {
+                    self.value = other;
+                }
+            },
+            _ => self.value += count_mode.round(other),
+        }
+        self.count += 1;
         self.text = count_mode.to_string(self.value);
     }
+
+    /// True if no value was added to the counter
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
 }
 
 
 
+/// Mean point of impact of the given shots, (0, 0) if there are no shots.
+/// shots:          shots to average
+/// return:         rounded mean of the x and y coordinates in 1/1000 mm
+pub fn mean_point(shots: &[&&Shot]) -> (i32, i32) {
+    match shots.len() {
+        0 => (0, 0),
+        n => {
+            let mean = |value: fn(&Shot) -> i32| {
+                let sum = shots.iter().map(|shot| f64::from(value(shot))).sum::<f64>();
+                (sum / n as f64).round() as i32
+            };
+            (mean(|shot| shot.x), mean(|shot| shot.y))
+        },
+    }
+}
 
 
 
 
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+
+
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum CountMode {
+    /// Sum of the integer rings
     Integer,
+    /// Sum of the rings with tenth
     Tenth,
+    /// Lowest teiler (multiplied with the teiler factor of the target)
+    Teiler,
+    /// Sum of the teilers (multiplied with the teiler factor of the target)
+    TeilerSum,
 }
 impl CountMode {
     /// Creates a string according to the CountMode
     /// value:  value to round
     /// return: rounded string
-    pub fn to_string(self, value: f64) -> String {
+    pub fn to_string(self, value: Score) -> String {
         match self {
-            CountMode::Integer => format!("{:.0}", value.floor()),
-            CountMode::Tenth => format!("{:.1}", value),
+            CountMode::Integer => value.to_integer_string(),
+            CountMode::Tenth | CountMode::Teiler | CountMode::TeilerSum => value.to_string(),
         }
     }
 
     /// Round given value according to the CountMode
     /// value:  value to round
     /// return: rounded value
-    pub fn round(self, value: f64) -> f64 {
+    pub fn round(self, value: Score) -> Score {
         match self {
             CountMode::Integer => value.floor(),
-            CountMode::Tenth => (value * 10.0).round() / 10.0,
+            CountMode::Tenth | CountMode::Teiler | CountMode::TeilerSum => value,
+        }
+    }
+
+    /// True if we count teilers, in this case lower values are better
+    pub fn is_teiler(self) -> bool {
+        match self {
+            CountMode::Teiler | CountMode::TeilerSum => true,
+            CountMode::Integer | CountMode::Tenth => false,
+        }
+    }
+
+    /// Compare two results counted with this CountMode
+    /// a, b:   values to compare
+    /// return: Ordering::Less if a is the better result, and should be ranked before b
+    pub fn compare(self, a: Score, b: Score) -> Ordering {
+        let ordering = a.cmp(&b);
+        match self.is_teiler() {
+            true => ordering,
+            false => ordering.reverse(),
+        }
+    }
+
+    /// Compare two results counted with this CountMode, a missing result (e.g. a part without
+    /// counted shots) is ranked last, also in the teiler modes where 0 would be the best value
+    /// a, b:   results to compare, None if there is no result
+    /// return: Ordering::Less if a is the better result, and should be ranked before b
+    pub fn compare_results(self, a: Option<Score>, b: Option<Score>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => self.compare(a, b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
         }
     }
 }
@@ -77,39 +157,88 @@ impl CountMode {
 
 #[cfg(test)]
 mod test {
+    use std::cmp::Ordering;
     use session::counter::{Counter, CountMode};
+    use session::Score;
 
     #[test]
     fn test_new_counter() {
-        let counter_int = Counter::new(9.3, &CountMode::Integer);
+        let counter_int = Counter::new(Score::from(9.3), &CountMode::Integer);
         assert_eq!("9".to_string(), counter_int.text);
-        assert_eq!(9.0, counter_int.value);
+        assert_eq!(Score::from(9.0), counter_int.value);
 
-        let counter_tenth = Counter::new(9.3, &CountMode::Tenth);
+        let counter_tenth = Counter::new(Score::from(9.3), &CountMode::Tenth);
         assert_eq!("9.3".to_string(), counter_tenth.text);
-        assert_eq!(9.3, counter_tenth.value);
+        assert_eq!(Score::from(9.3), counter_tenth.value);
     }
 
     #[test]
     fn test_add_int() {
-        let mut counter = Counter::new(9.3, &CountMode::Integer);
+        let mut counter = Counter::new(Score::from(9.3), &CountMode::Integer);
         assert_eq!("9".to_string(), counter.text);
-        assert_eq!(9.0, counter.value);
+        assert_eq!(Score::from(9.0), counter.value);
 
-        counter.add(1.6, &CountMode::Integer);
+        counter.add(Score::from(1.6), &CountMode::Integer);
         assert_eq!("10".to_string(), counter.text);
-        assert_eq!(10.0, counter.value);
+        assert_eq!(Score::from(10.0), counter.value);
     }
 
     #[test]
     fn test_add_tenth() {
-        let mut counter = Counter::new(9.3, &CountMode::Tenth);
+        let mut counter = Counter::new(Score::from(9.3), &CountMode::Tenth);
         assert_eq!("9.3".to_string(), counter.text);
-        assert_eq!(9.3, counter.value);
+        assert_eq!(Score::from(9.3), counter.value);
 
-        counter.add(1.7, &CountMode::Tenth);
+        counter.add(Score::from(1.7), &CountMode::Tenth);
         assert_eq!("11.0".to_string(), counter.text);
-        assert_eq!(11.0, counter.value);
+        assert_eq!(Score::from(11.0), counter.value);
+    }
+
+    #[test]
+    fn test_add_teiler() {
+        let mut counter = Counter::empty();
+        counter.add(Score::from(123.4), &CountMode::Teiler);
+        assert_eq!("123.4".to_string(), counter.text);
+        assert_eq!(Score::from(123.4), counter.value);
+
+        counter.add(Score::from(300.0), &CountMode::Teiler);
+        assert_eq!(Score::from(123.4), counter.value);
+
+        counter.add(Score::from(12.3), &CountMode::Teiler);
+        assert_eq!("12.3".to_string(), counter.text);
+        assert_eq!(Score::from(12.3), counter.value);
+    }
+
+    #[test]
+    fn test_add_teiler_sum() {
+        let mut counter = Counter::empty();
+        counter.add(Score::from(100.0), &CountMode::TeilerSum);
+        counter.add(Score::from(23.4), &CountMode::TeilerSum);
+        assert_eq!("123.4".to_string(), counter.text);
+        assert_eq!(Score::from(123.4), counter.value);
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(Ordering::Less, CountMode::Tenth.compare(Score::from(100.1), Score::from(99.0)));
+        assert_eq!(Ordering::Greater, CountMode::Integer.compare(Score::from(98.0), Score::from(99.0)));
+        assert_eq!(Ordering::Less, CountMode::Teiler.compare(Score::from(12.3), Score::from(99.0)));
+        assert_eq!(Ordering::Greater, CountMode::TeilerSum.compare(Score::from(512.3), Score::from(99.0)));
+        assert_eq!(Ordering::Equal, CountMode::Teiler.compare(Score::from(12.3), Score::from(12.3)));
+    }
+
+    #[test]
+    fn test_compare_results() {
+        // Without a result a line is ranked last, although 0 is the best teiler
+        assert_eq!(Ordering::Less, CountMode::Teiler.compare_results(Some(Score::from(512.3)), None));
+        assert_eq!(Ordering::Greater, CountMode::TeilerSum.compare_results(None, Some(Score::from(512.3))));
+        assert_eq!(Ordering::Less, CountMode::Integer.compare_results(Some(Score::zero()), None));
+        assert_eq!(Ordering::Equal, CountMode::Teiler.compare_results(None, None));
+
+        let mut counter = Counter::empty();
+        assert!(counter.is_empty());
+        counter.add(Score::zero(), &CountMode::Teiler);
+        assert!(!counter.is_empty());
     }
 
 }
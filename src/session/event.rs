@@ -0,0 +1,177 @@
+use std::time::SystemTime;
+
+use super::{PartType, ActivePart, User, Club, Team, Equipment, Score};
+use registry::Shooter;
+use discipline::{Run, Game};
+
+
+
+/// Events changing a session. The session stores all events in an append only log, all other
+/// values of the session (parts, series, sums) are derived from this log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum Event {
+    /// New shot from the device, coordinates in 1/1000 mm
+    ShotAdded { x: i32, y: i32 },
+
+    /// Change to the part with the given type
+    PartChanged { part_type: PartType, force: bool },
+
+    /// Change to the part with the given index
+    ActivePartChanged { index: ActivePart, force: bool },
+
+    /// New target in the active part
+    NewTarget,
+
+    /// Invalidate a shot, the shot is referenced by the index of its ShotAdded event
+    ShotInvalidated { shot_id: usize },
+
+    /// Move the shots with the given ids to the part with the given index
+    ShotsMoved { shot_ids: Vec<usize>, to_part: ActivePart },
+
+    /// Assign a user to the session
+    UserAssigned { user: User },
+
+    /// Assign a club to the session
+    ClubAssigned { club: Club },
+
+    /// Assign a team to the session
+    TeamAssigned { team: Team },
+
+    /// Assign a shooter from the registry, sets user, club and team of the session
+    ShooterAssigned { shooter: Shooter },
+
+    /// Add a shooter to a shared line, the new shooter becomes the active one
+    ShooterAdded {
+        user: User,
+        #[serde(default="Club::empty")] club: Club,
+        #[serde(default="Team::empty")] team: Team,
+    },
+
+    /// Change the active shooter of a shared line, shots are attributed to the active shooter
+    ShooterSwitched { shooter: usize },
+
+    /// Set the tag with the given key, a tag without value is removed
+    TagSet { key: String, value: Option<String> },
+
+    /// Set the equipment used in the session
+    EquipmentChanged { equipment: Equipment },
+
+    /// Deduct points from the part with the given index
+    PenaltyAdded { part: ActivePart, amount: Score, reason: String, officer: String },
+
+    /// Select the run of the moving target for the next shots, None to use the runs of the
+    /// discipline part
+    RunSelected { run: Option<Run> },
+
+    /// Correct the run of the shot with the given id
+    ShotRunChanged { shot_id: usize, run: Run },
+
+    /// Stop the time limits of the session and the active part, e.g. for a range incident
+    TimerPaused { reason: Option<String> },
+
+    /// Continue the time limits after a pause
+    TimerResumed,
+
+    /// End the sighting period of the active final stage
+    FinalStageStarted,
+
+    /// Start a training game, the following shots are played in the game
+    GameStarted { game: Game },
+
+    /// Stop the running game
+    GameStopped,
+
+    /// Undo the last operator action, which was not undone yet
+    Undo,
+
+    /// The session was resumed after a restart of DSC
+    SessionResumed,
+
+    /// The session was finished, e.g. because a new discipline was selected
+    SessionFinished,
+
+    /// The result was signed off, no further events are accepted. The hash covers all events
+    /// before, and the hash of the previous finalized session of the line.
+    SessionFinalized { signed_off_by: String, hash: String, previous_hash: Option<String> },
+}
+
+impl Event {
+    /// Check if the event is an action of the operator, which can be undone. Shots from the
+    /// device can not be undone, they have to be invalidated.
+    pub fn is_operator_action(&self) -> bool {
+        match *self {
+            Event::ShotAdded { .. } | Event::Undo | Event::SessionResumed |
+            Event::SessionFinished | Event::SessionFinalized { .. } => false,
+            Event::PartChanged { .. } | Event::ActivePartChanged { .. } | Event::NewTarget |
+            Event::ShotInvalidated { .. } | Event::ShotsMoved { .. } | Event::UserAssigned { .. } |
+            Event::ClubAssigned { .. } | Event::TeamAssigned { .. } |
+            Event::ShooterAssigned { .. } | Event::ShooterAdded { .. } |
+            Event::ShooterSwitched { .. } | Event::TagSet { .. } |
+            Event::EquipmentChanged { .. } | Event::PenaltyAdded { .. } |
+            Event::RunSelected { .. } | Event::ShotRunChanged { .. } | Event::TimerPaused { .. } |
+            Event::TimerResumed | Event::FinalStageStarted | Event::GameStarted { .. } |
+            Event::GameStopped => true,
+        }
+    }
+}
+
+
+
+/// Entry of the event log of a session
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogEntry {
+    pub date: SystemTime,
+    pub event: Event,
+}
+
+
+
+/// Return a flag for each entry of the log, if it was undone (or is an undo event itself).
+/// Each undo event cancels the last operator action, which was not undone before.
+pub fn get_undone_entries(events: &[LogEntry]) -> Vec<bool> {
+    let mut undone = vec![false; events.len()];
+    let mut operator_actions: Vec<usize> = vec![];
+    for (i, entry) in events.iter().enumerate() {
+        match entry.event {
+            Event::Undo => {
+                undone[i] = true;
+                if let Some(index) = operator_actions.pop() {
+                    undone[index] = true;
+                }
+            },
+            ref event if event.is_operator_action() => operator_actions.push(i),
+            _ => {},
+        }
+    }
+    undone
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+    use session::event::*;
+
+    fn entry(event: Event) -> LogEntry {
+        LogEntry { date: SystemTime::now(), event }
+    }
+
+    #[test]
+    fn test_undone_entries() {
+        let events = vec![
+            entry(Event::ShotAdded { x: 0, y: 0 }),
+            entry(Event::NewTarget),
+            entry(Event::ShotAdded { x: 0, y: 0 }),
+            entry(Event::FinalStageStarted),
+            entry(Event::Undo),
+            entry(Event::Undo),
+            entry(Event::Undo),
+        ];
+        let undone = get_undone_entries(&events);
+        assert_eq!(vec![false, true, false, true, true, true, true], undone);
+    }
+}
@@ -0,0 +1,48 @@
+use discipline::TargetFace;
+use super::{Shot, Counter, CountMode};
+use super::counter::mean_point;
+
+
+
+/// Statistics of the shots on one face of a lane with several faces
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FaceStatistics {
+    pub face: usize,
+    pub name: String,
+    pub number_of_shots: usize,
+    /// Number of shots scored as misses, e.g. a second hit on the face in one series
+    pub number_of_annulled: usize,
+    pub sum: Counter,
+    /// Mean point of impact in 1/1000 mm, relative to the centre of the face
+    pub mpi_x: i32,
+    pub mpi_y: i32,
+}
+
+impl FaceStatistics {
+    /// Calculate the statistics of each face
+    /// shots:          shots of the part
+    /// faces:          faces of the discipline, a lane without faces has a single face
+    /// count_mode:     count mode of the part
+    pub fn new(shots: &[&Shot], faces: &[TargetFace], count_mode: &CountMode) -> Vec<FaceStatistics> {
+        let number_of_faces = faces.len().max(1);
+        (0..number_of_faces)
+            .map(|face| {
+                let shots: Vec<&&Shot> = shots.iter().filter(|shot| shot.face == face).collect();
+                let mut sum = Counter::empty();
+                for shot in &shots {
+                    sum.add(shot.ring_count, count_mode);
+                }
+                let (mpi_x, mpi_y) = mean_point(&shots);
+                FaceStatistics {
+                    face,
+                    name: faces.get(face).map_or_else(String::new, |face| face.name.clone()),
+                    number_of_shots: shots.len(),
+                    number_of_annulled: shots.iter().filter(|shot| shot.annulled).count(),
+                    sum,
+                    mpi_x,
+                    mpi_y,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,390 @@
+use std::time::SystemTime;
+
+use discipline::*;
+use super::{Shot, Session, Info, Timer, Pause, Score};
+
+
+
+/// Phase of a final
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum FinalPhase {
+    /// Sighting period before a stage, shots are not counted
+    Sighting,
+    /// Shots of the stage are counted
+    Stage,
+    /// All stages are done, further shots are ignored
+    Finished,
+}
+
+
+
+/// State of a part in final mode, contains the active stage and its timer, as well as the
+/// cumulative score after each shot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FinalState {
+    /// Index of the active stage
+    pub stage: usize,
+    pub phase: FinalPhase,
+    /// Timer of the sighting period or the current series
+    pub timer: Option<Timer>,
+    /// Cumulative score after each counted shot
+    pub progression: Vec<Score>,
+    /// Shots fired in the sighting periods
+    pub sighting_shots: Vec<Shot>,
+}
+
+impl FinalState {
+    /// New final, starting with the first stage
+    /// stages:     stages of the final
+    /// now:        date to start the first timer at
+    pub fn new(stages: &[FinalStage], now: SystemTime) -> FinalState {
+        let mut state = FinalState {
+            stage: 0,
+            phase: FinalPhase::Finished,
+            timer: None,
+            progression: vec![],
+            sighting_shots: vec![],
+        };
+        state.enter_stage(stages, 0, now);
+        state
+    }
+
+    /// Enter the stage with the given index, starts with the sighting period if the stage has one
+    fn enter_stage(&mut self, stages: &[FinalStage], index: usize, now: SystemTime) {
+        self.stage = index;
+        match stages.get(index) {
+            Some(&FinalStage { sighting_time: Some(sighting_time), .. }) => {
+                self.phase = FinalPhase::Sighting;
+                self.timer = Some(Timer::new_at(now, sighting_time));
+            },
+            Some(stage) => {
+                self.phase = FinalPhase::Stage;
+                self.timer = Some(Timer::new_at(now, stage.series_time));
+            },
+            None => {
+                self.phase = FinalPhase::Finished;
+                self.timer = None;
+            },
+        }
+    }
+
+    /// End the sighting period of the active stage and start counting
+    /// stages:     stages of the final
+    /// now:        date to start the series timer at
+    pub fn start_stage(&mut self, stages: &[FinalStage], now: SystemTime) {
+        if let FinalPhase::Sighting = self.phase {
+            if let Some(stage) = stages.get(self.stage) {
+                self.phase = FinalPhase::Stage;
+                self.timer = Some(Timer::new_at(now, stage.series_time));
+            }
+        }
+    }
+
+    /// Start the stage, if the sighting period is over
+    /// stages:     stages of the final
+    /// now:        current date
+    /// pauses:     pauses of the session, the timers are stopped during the pauses
+    /// return:     true if the phase changed
+    pub fn update(&mut self, stages: &[FinalStage], now: SystemTime, pauses: &[Pause]) -> bool {
+        let sighting_over = match (&self.phase, &self.timer) {
+            (&FinalPhase::Sighting, &Some(ref timer)) => timer.is_over(now, pauses),
+            _ => false,
+        };
+        if sighting_over {
+            self.start_stage(stages, now);
+        }
+        sighting_over
+    }
+
+    /// Check if a shot at the given date is late, because the time of the current series is over
+    pub fn is_late(&self, now: SystemTime, pauses: &[Pause]) -> bool {
+        match (&self.phase, &self.timer) {
+            (&FinalPhase::Stage, &Some(ref timer)) => timer.is_over(now, pauses),
+            _ => false,
+        }
+    }
+
+    /// Check if shots are counted in the current phase
+    pub fn is_counting(&self) -> bool {
+        match self.phase {
+            FinalPhase::Stage => true,
+            FinalPhase::Sighting | FinalPhase::Finished => false,
+        }
+    }
+
+    /// Add a shot which is not counted, sighting shots are stored, shots after the final are
+    /// ignored.
+    pub fn add_uncounted_shot(&mut self, shot: Shot) {
+        match self.phase {
+            FinalPhase::Sighting => self.sighting_shots.push(shot),
+            _ => println!("Final finished, shot ignored"),
+        }
+    }
+
+    /// Add the cumulative score after a counted shot, and start the next series or stage if the
+    /// current one is complete.
+    /// stages:     stages of the final
+    /// sum:        sum of the part, including the new shot
+    /// now:        date of the shot
+    pub fn add_counted_shot(&mut self, stages: &[FinalStage], sum: Score, now: SystemTime) {
+        self.progression.push(sum);
+        let number_of_shots = self.progression.len() as i32;
+        let first_shot: i32 = stages.iter().take(self.stage).map(|stage| stage.number_of_shots).sum();
+
+        if let Some(stage) = stages.get(self.stage) {
+            let shots_in_stage = number_of_shots - first_shot;
+            if shots_in_stage >= stage.number_of_shots {
+                let next_stage = self.stage + 1;
+                self.enter_stage(stages, next_stage, now);
+            }
+            else if stage.series_length > 0 && shots_in_stage % stage.series_length == 0 {
+                self.timer = Some(Timer::new_at(now, stage.series_time));
+            }
+        }
+    }
+
+    /// Return the cumulative score after the given number of shots
+    pub fn get_score(&self, number_of_shots: i32) -> Option<Score> {
+        if number_of_shots <= 0 {
+            return None;
+        }
+        self.progression.get(number_of_shots as usize - 1).cloned()
+    }
+}
+
+
+
+/// Rank of one line in a final
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FinalRank {
+    pub session_id: String,
+    pub info: Info,
+    pub rank: usize,
+    /// Score the rank is based on, the score at the elimination or the current score
+    pub score: Score,
+    pub number_of_shots: i32,
+    /// Index of the stage after which the line was eliminated
+    pub eliminated_after: Option<usize>,
+}
+
+
+
+/// Rank the finals of several lines. After each stage with eliminations, the worst lines are
+/// eliminated and keep the rank they had at this point. The remaining lines are ranked by their
+/// current score. Eliminations are only applied when all remaining lines finished the stage.
+/// sessions:   sessions of all lines, sessions without a final part are skipped
+/// return:     ranking, best line first
+pub fn rank_finals(sessions: &[Session]) -> Vec<FinalRank> {
+    // Final state and discipline part of each session
+    let finals: Vec<(&Session, &FinalState, &DisciplinePart)> = sessions.iter()
+        .filter_map(|session| match session.get_final_part() {
+            Some((part, discipline_part)) => match part.final_state {
+                Some(ref final_state) => Some((session, final_state, discipline_part)),
+                None => None,
+            },
+            None => None,
+        })
+        .collect();
+
+    let (stages, count_mode) = match finals.first() {
+        Some(&(_, _, discipline_part)) => match discipline_part.mode {
+            PartMode::Final { ref stages } => (stages.clone(), discipline_part.count_mode),
+            PartMode::Default | PartMode::LotTest { .. } => return vec![],
+        },
+        None => return vec![],
+    };
+
+    let new_rank = |index: usize, score: Score, eliminated_after: Option<usize>| {
+        let (session, final_state, _) = finals[index];
+        FinalRank {
+            session_id: session.id.clone(),
+            info: session.info.clone(),
+            rank: 0,
+            score,
+            number_of_shots: final_state.progression.len() as i32,
+            eliminated_after,
+        }
+    };
+
+    let mut active: Vec<usize> = (0..finals.len()).collect();
+    let mut eliminated: Vec<FinalRank> = vec![];
+    let mut shots_after_stage = 0;
+    for (stage_index, stage) in stages.iter().enumerate() {
+        shots_after_stage += stage.number_of_shots;
+        if stage.eliminations == 0 {
+            continue;
+        }
+        let scores: Vec<Option<Score>> = active.iter()
+            .map(|index| finals[*index].1.get_score(shots_after_stage))
+            .collect();
+        if scores.iter().any(|score| score.is_none()) {
+            break;
+        }
+
+        let mut stage_ranking: Vec<(usize, Score)> = active.iter().cloned()
+            .zip(scores.into_iter().map(|score| score.unwrap()))
+            .collect();
+        stage_ranking.sort_by(|a, b| {
+            count_mode.compare(a.1, b.1).then_with(|| finals[a.0].0.compare(finals[b.0].0))
+        });
+
+        let number_of_eliminations = stage.eliminations.min(stage_ranking.len());
+        let split = stage_ranking.len() - number_of_eliminations;
+        for &(index, score) in stage_ranking[split..].iter().rev() {
+            eliminated.push(new_rank(index, score, Some(stage_index)));
+        }
+        active = stage_ranking[..split].iter().map(|&(index, _)| index).collect();
+    }
+
+    // Equal scores of the remaining lines are ranked by the tie break rules of the discipline,
+    // lines without a shot are ranked last
+    let mut active_ranking: Vec<(usize, Option<Score>)> = active.iter()
+        .map(|index| (*index, finals[*index].1.progression.last().cloned()))
+        .collect();
+    active_ranking.sort_by(|a, b| {
+        count_mode.compare_results(a.1, b.1).then_with(|| finals[a.0].0.compare(finals[b.0].0))
+    });
+    let mut ranking: Vec<FinalRank> = active_ranking.into_iter()
+        .map(|(index, score)| new_rank(index, score.unwrap_or_else(Score::zero), None))
+        .collect();
+    ranking.extend(eliminated.into_iter().rev());
+
+    for (i, rank) in ranking.iter_mut().enumerate() {
+        rank.rank = i + 1;
+    }
+    ranking
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::time::{SystemTime, Duration};
+    use session::final_mode::*;
+    use session::{Session, Line, ShotRaw, AddShotRaw, Update, Score};
+    use discipline::TieBreak;
+    use helper;
+
+    fn get_stages() -> Vec<FinalStage> {
+        vec![
+            FinalStage {
+                name: "Series".to_string(), number_of_shots: 4, series_length: 2,
+                series_time: 100, sighting_time: Some(60), eliminations: 0,
+            },
+            FinalStage {
+                name: "Elimination".to_string(), number_of_shots: 1, series_length: 1,
+                series_time: 50, sighting_time: None, eliminations: 1,
+            },
+            FinalStage {
+                name: "Medal".to_string(), number_of_shots: 1, series_length: 1,
+                series_time: 50, sighting_time: None, eliminations: 1,
+            },
+        ]
+    }
+
+    fn get_final_session(id: &str) -> Session {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].mode = PartMode::Final { stages: get_stages() };
+        let mut session = Session::new(id.to_string(), Line::demo(), discipline);
+        session.start_final_stage();
+        session
+    }
+
+    #[test]
+    fn test_stages() {
+        let stages = get_stages();
+        let now = SystemTime::now();
+        let mut state = FinalState::new(&stages, now);
+        assert!(!state.is_counting());
+
+        assert!(!state.update(&stages, now, &[]));
+        assert!(state.update(&stages, now + Duration::from_secs(61), &[]));
+        assert!(state.is_counting());
+
+        for i in 0..3 {
+            state.add_counted_shot(&stages, Score::from_integer(i), now);
+            assert_eq!(0, state.stage);
+        }
+        state.add_counted_shot(&stages, Score::from(5.0), now);
+        assert_eq!(1, state.stage);
+        state.add_counted_shot(&stages, Score::from(10.0), now);
+        assert_eq!(2, state.stage);
+        assert!(state.is_counting());
+        state.add_counted_shot(&stages, Score::from(20.0), now);
+        assert!(!state.is_counting());
+        assert_eq!(Some(Score::from(10.0)), state.get_score(5));
+    }
+
+    #[test]
+    fn test_sighting_shots() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].mode = PartMode::Final { stages: get_stages() };
+        let mut session = Session::new("0".to_string(), Line::demo(), discipline);
+
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        let (part, _) = session.get_final_part().unwrap();
+        let final_state = part.final_state.clone().unwrap();
+        assert_eq!(1, final_state.sighting_shots.len());
+        assert_eq!(0, final_state.progression.len());
+
+        session.start_final_stage();
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        let (part, _) = session.get_final_part().unwrap();
+        assert_eq!(2, part.series.len());
+        assert_eq!(vec![Score::from(10.9), Score::from(21.8), Score::from(32.7)], part.final_state.clone().unwrap().progression);
+    }
+
+    #[test]
+    fn test_rank_finals() {
+        // a: 4x 10.9 + 10.0 + 9.0, b: 4x 10.9 + 9.0, c: 4x 10.9 + 10.0 + 10.0
+        let shots = vec![
+            ("a", vec![0, 0, 0, 0, 2500, 5000]),
+            ("b", vec![0, 0, 0, 0, 5000]),
+            ("c", vec![0, 0, 0, 0, 2500, 2500]),
+        ];
+        let sessions: Vec<Session> = shots.iter().map(|&(id, ref xs)| {
+            let mut session = get_final_session(id);
+            for x in xs {
+                session.add_shot_raw(ShotRaw { x: *x, y: 0 });
+            }
+            session
+        }).collect();
+
+        let ranking = rank_finals(&sessions);
+        let ids: Vec<String> = ranking.iter().map(|rank| rank.session_id.clone()).collect();
+        assert_eq!(vec!["c", "a", "b"], ids);
+        assert_eq!(None, ranking[0].eliminated_after);
+        assert_eq!(Some(2), ranking[1].eliminated_after);
+        assert_eq!(Some(1), ranking[2].eliminated_after);
+        assert_eq!(3, ranking[2].rank);
+    }
+
+    #[test]
+    fn test_rank_finals_elimination_tie() {
+        // a and b have the same score at the elimination, a has the better last series
+        let shots = vec![
+            ("b", vec![0, 0, 0, 2500, 0]),
+            ("a", vec![0, 2500, 0, 0, 0]),
+            ("c", vec![0, 0, 0, 0, 0]),
+        ];
+        let sessions: Vec<Session> = shots.iter().map(|&(id, ref xs)| {
+            let mut session = get_final_session(id);
+            session.discipline.tie_break = vec![TieBreak::Total, TieBreak::LastSeries];
+            for x in xs {
+                session.add_shot_raw(ShotRaw { x: *x, y: 0 });
+            }
+            session
+        }).collect();
+
+        let ranking = rank_finals(&sessions);
+        assert_eq!(ranking[1].score, ranking[2].score);
+        let ids: Vec<String> = ranking.iter().map(|rank| rank.session_id.clone()).collect();
+        assert_eq!(vec!["c", "a", "b"], ids);
+        assert_eq!(Some(1), ranking[2].eliminated_after);
+    }
+}
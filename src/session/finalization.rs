@@ -0,0 +1,163 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::SystemTime;
+use serde_json;
+use sha2::{Sha256, Digest};
+
+use super::Session;
+use super::event::{Event, LogEntry};
+
+
+
+/// Sign-off of a finalized session. The hash covers all events before the sign-off, everything the
+/// session is scored with (discipline, target, line), and the hash of the previous finalized
+/// session of the line, so the finalized sessions of a line form a chain.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Finalization {
+    pub signed_off_by: String,
+    pub date: SystemTime,
+    pub hash: String,
+    pub previous_hash: Option<String>,
+}
+
+/// Calculate the hash of a finalized session. All values the session is rebuilt from are hashed,
+/// so changing e.g. the stored target changes the hash as well.
+/// session:        session to hash, its id, discipline, line and creation date are hashed
+/// events:         all events before the sign-off
+/// signed_off_by:  name of the person who signed off the result
+/// date:           date of the sign-off
+/// previous_hash:  hash of the previous finalized session of the line
+pub fn compute_hash(session: &Session, events: &[LogEntry], signed_off_by: &str, date: SystemTime, previous_hash: &Option<String>) -> String {
+    let content = serde_json::to_string(&(
+        &session.id, &session.discipline, &session.info.line, session.get_created(),
+        events, signed_off_by, date, previous_hash,
+    )).expect("sessions can always be serialized");
+    Sha256::digest(content.as_bytes()).iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Verify, that the stored events of the session match the hash of its sign-off
+pub fn verify_session(session: &Session) -> Result<(), Error> {
+    let index = session.events.iter()
+        .position(|entry| match entry.event {
+            Event::SessionFinalized { .. } => true,
+            _ => false,
+        })
+        .ok_or(Error::NotFinalized)?;
+
+    match session.events[index] {
+        LogEntry { date, event: Event::SessionFinalized { ref signed_off_by, ref hash, ref previous_hash } } => {
+            let expected = compute_hash(session, &session.events[..index], signed_off_by, date, previous_hash);
+            if *hash != expected || index + 1 != session.events.len() {
+                return Err(Error::HashMismatch(session.id.clone()));
+            }
+            Ok(())
+        },
+        _ => Err(Error::NotFinalized),
+    }
+}
+
+/// Verify the finalized sessions of a line, each session has to be valid and reference the hash
+/// of the session before.
+/// sessions:   finalized sessions of the line, in the order they were finalized
+pub fn verify_chain(sessions: &[Session]) -> Result<(), Error> {
+    let mut previous_hash: Option<String> = None;
+    for session in sessions {
+        verify_session(session)?;
+        let finalization = session.finalization.as_ref().ok_or(Error::NotFinalized)?;
+        if finalization.previous_hash != previous_hash {
+            return Err(Error::ChainBroken(session.id.clone()));
+        }
+        previous_hash = Some(finalization.hash.clone());
+    }
+    Ok(())
+}
+
+
+
+#[derive(Debug)]
+pub enum Error {
+    NotFinalized,
+    HashMismatch(String),
+    ChainBroken(String),
+}
+
+impl StdError for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::NotFinalized => "Session not finalized",
+            Error::HashMismatch(_) => "Hash mismatch",
+            Error::ChainBroken(_) => "Chain broken",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NotFinalized => write!(f, "Session is not finalized"),
+            Error::HashMismatch(ref id) =>
+                write!(f, "Session {} was altered after it was finalized", id),
+            Error::ChainBroken(ref id) =>
+                write!(f, "Session {} does not reference the previous finalized session", id),
+        }
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use session::finalization::*;
+    use session::{Session, Line, ShotRaw, AddShotRaw, Update};
+    use session::event::Event;
+    use helper;
+
+    fn finalized_session(id: &str, previous_hash: Option<String>) -> Session {
+        let discipline = helper::dsc_demo::lg_discipline();
+        let mut session = Session::new(id.to_string(), Line::demo(), discipline);
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.finalize("Max Mustermann".to_string(), previous_hash);
+        session
+    }
+
+    #[test]
+    fn test_verify_session() {
+        let mut session = finalized_session("a", None);
+        assert!(verify_session(&session).is_ok());
+
+        // No changes after finalization
+        assert!(session.is_finalized());
+        assert!(!session.finalize("Max Mustermann".to_string(), None));
+        session.add_shot_raw(ShotRaw { x: 100, y: 0 });
+        session.undo();
+        assert_eq!(2, session.events.len());
+
+        let mut altered = session.clone();
+        altered.events[0].event = Event::ShotAdded { x: 1, y: 0 };
+        assert!(verify_session(&altered).is_err());
+
+        // The target the session is scored with is covered as well
+        let mut altered = session.clone();
+        altered.discipline.target.bullet_diameter = 4.6;
+        assert!(verify_session(&altered).is_err());
+        let mut altered = session.clone();
+        altered.info.line.id = 2;
+        assert!(verify_session(&altered).is_err());
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let a = finalized_session("a", None);
+        let hash = a.finalization.clone().unwrap().hash;
+        let b = finalized_session("b", Some(hash));
+        assert!(verify_chain(&[a.clone(), b.clone()]).is_ok());
+        assert!(verify_chain(&[b.clone()]).is_err());
+
+        let c = finalized_session("c", None);
+        assert!(verify_chain(&[a, c]).is_err());
+    }
+}
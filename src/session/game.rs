@@ -0,0 +1,255 @@
+use std::time::SystemTime;
+
+use discipline::{Game, GameRules};
+use discipline::game::get_sector;
+use super::{Shot, Counter, Score};
+
+
+
+/// Status of a game
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum GameStatus {
+    Running,
+    /// The end condition of the game was reached
+    Finished,
+    /// The shooter missed a step of the elimination ladder
+    Eliminated,
+}
+
+/// Progress of a game, depending on its rules
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum GameProgress {
+    AroundTheClock { next_sector: usize },
+    EliminationLadder { step: usize },
+    ScoreRace { sum: Counter },
+    BestOf { best: Option<Score> },
+}
+
+/// State of a running or finished game
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameState {
+    pub game: Game,
+    pub progress: GameProgress,
+    pub status: GameStatus,
+    pub number_of_shots: usize,
+    /// Ids of the shots, which counted for the game (e.g. a hit in the next sector)
+    pub hits: Vec<usize>,
+    /// Progress or result of the game, as shown to the shooter
+    pub text: String,
+    pub started: SystemTime,
+}
+
+impl GameState {
+    /// New game, without shots
+    /// game:   definition of the game
+    /// date:   date the game was started
+    pub fn new(game: Game, date: SystemTime) -> GameState {
+        let progress = match game.rules {
+            GameRules::AroundTheClock { .. } => GameProgress::AroundTheClock { next_sector: 0 },
+            GameRules::EliminationLadder { .. } => GameProgress::EliminationLadder { step: 0 },
+            GameRules::ScoreRace { .. } => GameProgress::ScoreRace { sum: Counter::empty() },
+            GameRules::BestOf { .. } => GameProgress::BestOf { best: None },
+        };
+        let mut state = GameState {
+            game,
+            progress,
+            status: GameStatus::Running,
+            number_of_shots: 0,
+            hits: vec![],
+            text: String::new(),
+            started: date,
+        };
+        state.update_status();
+        state
+    }
+
+    /// True if the end condition of the game was not reached yet
+    pub fn is_running(&self) -> bool {
+        self.status == GameStatus::Running
+    }
+
+    /// Play the given shot, shots after the end of the game are ignored. Annulled shots are
+    /// played as a miss.
+    pub fn add_shot(&mut self, shot: &Shot) {
+        if !self.is_running() {
+            return;
+        }
+        self.number_of_shots += 1;
+        let ring = if shot.annulled { Score::zero() } else { shot.ring };
+
+        let hit = match (&self.game.rules, &mut self.progress) {
+            (&GameRules::AroundTheClock { sectors, min_ring }, &mut GameProgress::AroundTheClock { ref mut next_sector }) => {
+                let hit = ring >= min_ring && get_sector(shot.angle, sectors) == *next_sector;
+                if hit {
+                    *next_sector += 1;
+                }
+                hit
+            },
+            (&GameRules::EliminationLadder { ref steps }, &mut GameProgress::EliminationLadder { ref mut step }) => {
+                let hit = steps.get(*step).map_or(false, |&min_ring| ring >= min_ring);
+                if hit {
+                    *step += 1;
+                }
+                else {
+                    self.status = GameStatus::Eliminated;
+                }
+                hit
+            },
+            (&GameRules::ScoreRace { count_mode, .. }, &mut GameProgress::ScoreRace { ref mut sum }) => {
+                // Counted with the ring count mode of the game, not with the mode of the
+                // discipline part, see Game::validate
+                sum.add(count_mode.round(ring), &count_mode);
+                true
+            },
+            (&GameRules::BestOf { .. }, &mut GameProgress::BestOf { ref mut best }) => {
+                let hit = best.map_or(true, |best| ring > best);
+                if hit {
+                    *best = Some(ring);
+                }
+                hit
+            },
+            _ => {
+                println!("ERROR - game progress does not match the rules");
+                false
+            },
+        };
+        if hit {
+            self.hits.push(shot.id);
+        }
+        self.update_status();
+    }
+
+    /// Check the end condition of the game and update the text
+    fn update_status(&mut self) {
+        let shots = self.number_of_shots;
+        let (finished, text) = match (&self.game.rules, &self.progress) {
+            (&GameRules::AroundTheClock { sectors, .. }, &GameProgress::AroundTheClock { next_sector }) =>
+                match next_sector >= sectors {
+                    true => (true, format!("All {} sectors in {} shots", sectors, shots)),
+                    false => (false, format!("Sector {} of {}", next_sector + 1, sectors)),
+                },
+            (&GameRules::EliminationLadder { ref steps }, &GameProgress::EliminationLadder { step }) =>
+                match steps.get(step) {
+                    _ if self.status == GameStatus::Eliminated =>
+                        (true, format!("Eliminated at step {} of {}", step + 1, steps.len())),
+                    Some(min_ring) => (false, format!("Step {} of {}: {}", step + 1, steps.len(), min_ring)),
+                    None => (true, format!("All {} steps", steps.len())),
+                },
+            (&GameRules::ScoreRace { target_score, count_mode }, &GameProgress::ScoreRace { ref sum }) =>
+                match sum.value >= target_score {
+                    true => (true, format!("{} reached in {} shots", count_mode.to_string(target_score), shots)),
+                    false => (false, format!("{} of {}", count_mode.to_string(sum.value), count_mode.to_string(target_score))),
+                },
+            (&GameRules::BestOf { number_of_shots }, &GameProgress::BestOf { best }) => {
+                let best = best.map_or_else(|| "-".to_string(), |best| best.to_string());
+                (shots >= number_of_shots, format!("Best {} ({} of {} shots)", best, shots, number_of_shots))
+            },
+            _ => (true, String::new()),
+        };
+        if finished && self.status == GameStatus::Running {
+            self.status = GameStatus::Finished;
+        }
+        self.text = text;
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+    use session::game::*;
+    use session::CountMode;
+    use helper;
+
+    fn game(rules: GameRules) -> GameState {
+        let game = Game { id: "game".to_string(), title: "Game".to_string(), rules };
+        GameState::new(game, SystemTime::now())
+    }
+
+    fn shot(x: i32, y: i32) -> Shot {
+        Shot::from_cartesian_coordinates(x, y, &helper::dsc_demo::lg_target(), &CountMode::Tenth)
+    }
+
+    #[test]
+    fn test_around_the_clock() {
+        let mut state = game(GameRules::AroundTheClock { sectors: 4, min_ring: Score::from_integer(5) });
+        assert_eq!("Sector 1 of 4", state.text);
+        // 12 to 3 o'clock, 3 to 6 o'clock, missed sector, 6 to 9 o'clock, too low, 9 to 12 o'clock
+        for &(x, y) in &[(1000, 1000), (1000, -1000), (1000, 1000), (-1000, -1000), (-20000, 1000), (-1000, 1000)] {
+            state.add_shot(&shot(x, y));
+        }
+        assert_eq!(GameStatus::Finished, state.status);
+        assert_eq!(6, state.number_of_shots);
+        assert_eq!(4, state.hits.len());
+        assert_eq!("All 4 sectors in 6 shots", state.text);
+    }
+
+    #[test]
+    fn test_elimination_ladder() {
+        let steps = vec![Score::from(9.0), Score::from(10.0), Score::from(10.5)];
+        let mut state = game(GameRules::EliminationLadder { steps });
+        state.add_shot(&shot(0, 0));
+        state.add_shot(&shot(2000, 0));
+        assert_eq!(GameStatus::Running, state.status);
+        assert_eq!("Step 3 of 3: 10.5", state.text);
+        state.add_shot(&shot(2000, 0));
+        assert_eq!(GameStatus::Eliminated, state.status);
+
+        // Shots after the end are ignored
+        state.add_shot(&shot(0, 0));
+        assert_eq!(3, state.number_of_shots);
+        assert_eq!(2, state.hits.len());
+    }
+
+    #[test]
+    fn test_score_race() {
+        let rules = GameRules::ScoreRace { target_score: Score::from_integer(30), count_mode: CountMode::Integer };
+        let mut state = game(rules);
+        state.add_shot(&shot(0, 0));
+        state.add_shot(&shot(0, 0));
+        assert_eq!("20 of 30", state.text);
+        let mut annulled = shot(0, 0);
+        annulled.annulled = true;
+        state.add_shot(&annulled);
+        assert_eq!(GameStatus::Running, state.status);
+        state.add_shot(&shot(0, 0));
+        assert_eq!(GameStatus::Finished, state.status);
+        assert_eq!("30 reached in 4 shots", state.text);
+    }
+
+    #[test]
+    fn test_score_race_count_mode() {
+        // The shots are scored in the integer mode of the discipline part, the game counts tenth
+        let shot = Shot::from_cartesian_coordinates(2501, 0, &helper::dsc_demo::lg_target(), &CountMode::Integer);
+        let rules = GameRules::ScoreRace { target_score: Score::from_integer(30), count_mode: CountMode::Tenth };
+        let mut state = game(rules);
+        state.add_shot(&shot);
+        state.add_shot(&shot);
+        assert_eq!("19.8 of 30.0", state.text);
+
+        let rules = GameRules::ScoreRace { target_score: Score::from_integer(30), count_mode: CountMode::Integer };
+        let mut state = game(rules);
+        state.add_shot(&self::shot(2501, 0));
+        state.add_shot(&self::shot(2501, 0));
+        assert_eq!("18 of 30", state.text);
+    }
+
+    #[test]
+    fn test_best_of() {
+        let mut state = game(GameRules::BestOf { number_of_shots: 3 });
+        state.add_shot(&shot(2000, 0));
+        state.add_shot(&shot(100, 0));
+        assert_eq!(GameStatus::Running, state.status);
+        state.add_shot(&shot(3000, 0));
+        assert_eq!(GameStatus::Finished, state.status);
+        match state.progress {
+            GameProgress::BestOf { best } => assert_eq!(Some(Score::from(10.9)), best),
+            ref other => panic!("unexpected progress {:?}", other),
+        }
+        assert_eq!("Best 10.9 (3 of 3 shots)", state.text);
+    }
+}
@@ -1,12 +1,18 @@
+use std::collections::BTreeMap;
+
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Info {
-    user: User,
-    club: Club,
-    team: Team,
-    line: Line,
-
+    pub user: User,
+    pub club: Club,
+    pub team: Team,
+    pub line: Line,
+    /// Free key/ value tags, e.g. to compare barrels or stocks
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    #[serde(default)]
+    pub equipment: Equipment,
 }
 
 impl Info {
@@ -16,6 +22,8 @@ impl Info {
             club: Club::empty(),
             team: Team::empty(),
             line,
+            tags: BTreeMap::new(),
+            equipment: Equipment::default(),
         }
     }
 }
@@ -24,10 +32,10 @@ impl Info {
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct User {
-    first_name: String,
-    last_name: String,
-    id: String,
+pub struct User {
+    pub first_name: String,
+    pub last_name: String,
+    pub id: String,
 }
 
 impl User {
@@ -51,9 +59,9 @@ impl User {
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct Club {
-    name: String,
-    id: String,
+pub struct Club {
+    pub name: String,
+    pub id: String,
 }
 
 impl Club {
@@ -68,9 +76,9 @@ impl Club {
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct Team {
-    name: String,
-    id: String,
+pub struct Team {
+    pub name: String,
+    pub id: String,
 }
 
 impl Team {
@@ -84,6 +92,19 @@ impl Team {
 
 
 
+/// Equipment and ammunition used in a session
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Equipment {
+    pub rifle: Option<String>,
+    pub ammo_lot: Option<String>,
+    /// Weight of the pellet or bullet in g
+    pub pellet_weight: Option<f64>,
+    /// Temperature in °C
+    pub temperature: Option<f64>,
+}
+
+
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Line {
     pub id: i32,
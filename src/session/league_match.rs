@@ -0,0 +1,227 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use super::{Session, User, Team, Score};
+use super::team_event::latest_session;
+
+
+
+/// Definition of a league match, the shooters of the home and away team are paired position by
+/// position and shoot duels against each other.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LeagueMatch {
+    pub id: String,
+    pub name: String,
+    /// Id of the discipline both teams have to shoot
+    pub discipline: String,
+    pub home: Team,
+    pub away: Team,
+    pub pairings: Vec<Pairing>,
+    /// Tags the sessions of the shooters have to have, e.g. the round of the league
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Shooters of both teams at one position
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pairing {
+    pub position: usize,
+    pub home: User,
+    pub away: User,
+}
+
+
+
+/// Side of a league match
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Home,
+    Away,
+}
+
+/// Duel of one position
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Duel {
+    pub position: usize,
+    pub home: User,
+    pub away: User,
+    pub home_session_id: Option<String>,
+    pub away_session_id: Option<String>,
+    pub home_result: Option<Score>,
+    pub away_result: Option<Score>,
+    /// Winner of the duel, None for a draw or if both shooters have no session
+    pub winner: Option<Side>,
+}
+
+/// Result of a league match
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LeagueMatchResult {
+    pub match_id: String,
+    pub name: String,
+    pub home: Team,
+    pub away: Team,
+    pub duels: Vec<Duel>,
+    /// False if no shooter of the match has a result yet
+    pub shot: bool,
+    /// Number of duels won by each team
+    pub home_points: usize,
+    pub away_points: usize,
+    /// Team points, 2 for a win and 1 for a draw, a match not shot has no team points
+    pub home_team_points: usize,
+    pub away_team_points: usize,
+    /// Sum of the results of all shooters of each team
+    pub home_total: Score,
+    pub away_total: Score,
+}
+
+impl LeagueMatch {
+    /// Calculate the result of the match. Each shooter is represented by the latest session of the
+    /// discipline. A duel is won by the better result, equal results are ranked by the tie break
+    /// rules of the discipline. A shooter without a result loses the duel. A match in which no
+    /// shooter has a result is not shot yet and gives no team points.
+    /// sessions:   sessions of the shooters, sessions of other disciplines or users are skipped
+    pub fn get_result(&self, sessions: &[Session]) -> LeagueMatchResult {
+        let mut pairings: Vec<&Pairing> = self.pairings.iter().collect();
+        pairings.sort_by_key(|pairing| pairing.position);
+
+        let duels: Vec<Duel> = pairings.into_iter()
+            .map(|pairing| {
+                let home = latest_session(sessions, &self.discipline, &pairing.home);
+                let away = latest_session(sessions, &self.discipline, &pairing.away);
+                let winner = match (home.as_ref(), away.as_ref()) {
+                    (Some(home), Some(away)) => match home.compare(away) {
+                        Ordering::Less => Some(Side::Home),
+                        Ordering::Greater => Some(Side::Away),
+                        Ordering::Equal => None,
+                    },
+                    (Some(_), None) => Some(Side::Home),
+                    (None, Some(_)) => Some(Side::Away),
+                    (None, None) => None,
+                };
+                Duel {
+                    position: pairing.position,
+                    home: pairing.home.clone(),
+                    away: pairing.away.clone(),
+                    home_session_id: home.as_ref().map(|session| session.id.clone()),
+                    away_session_id: away.as_ref().map(|session| session.id.clone()),
+                    home_result: home.as_ref().and_then(|session| session.get_result()),
+                    away_result: away.as_ref().and_then(|session| session.get_result()),
+                    winner,
+                }
+            })
+            .collect();
+
+        let points = |side: Side| duels.iter().filter(|duel| duel.winner == Some(side)).count();
+        let total = |result: fn(&Duel) -> Option<Score>| duels.iter().filter_map(result).sum();
+        let shot = duels.iter().any(|duel| duel.home_result.is_some() || duel.away_result.is_some());
+        let home_points = points(Side::Home);
+        let away_points = points(Side::Away);
+        let (home_team_points, away_team_points) = match home_points.cmp(&away_points) {
+            _ if !shot => (0, 0),
+            Ordering::Greater => (2, 0),
+            Ordering::Less => (0, 2),
+            Ordering::Equal => (1, 1),
+        };
+
+        LeagueMatchResult {
+            match_id: self.id.clone(),
+            name: self.name.clone(),
+            home: self.home.clone(),
+            away: self.away.clone(),
+            home_total: total(|duel| duel.home_result),
+            away_total: total(|duel| duel.away_result),
+            duels,
+            shot,
+            home_points,
+            away_points,
+            home_team_points,
+            away_team_points,
+        }
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use session::league_match::*;
+    use session::{Session, Line, ShotRaw, AddShotRaw, CountMode, Update};
+    use helper;
+
+    fn user(id: &str) -> User {
+        User { first_name: id.to_string(), last_name: "".to_string(), id: id.to_string() }
+    }
+
+    fn session(id: &str, user_id: &str, x: i32) -> Session {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].count_mode = CountMode::Integer;
+        let mut session = Session::new(id.to_string(), Line::demo(), discipline);
+        session.set_user(user(user_id));
+        session.add_shot_raw(ShotRaw { x, y: 0 });
+        session
+    }
+
+    fn league_match() -> LeagueMatch {
+        let pairing = |position, home: &str, away: &str| Pairing {
+            position, home: user(home), away: user(away),
+        };
+        LeagueMatch {
+            id: "0".to_string(),
+            name: "Round 1".to_string(),
+            discipline: helper::dsc_demo::lg_discipline().id,
+            home: Team { name: "Home".to_string(), id: "1".to_string() },
+            away: Team { name: "Away".to_string(), id: "2".to_string() },
+            pairings: vec![pairing(2, "3", "4"), pairing(1, "1", "2"), pairing(3, "5", "6")],
+            tags: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_result() {
+        let sessions = vec![
+            session("a", "1", 0),
+            session("b", "2", 5000),
+            session("c", "3", 5000),
+            session("d", "4", 0),
+            session("e", "5", 0),
+        ];
+        let result = league_match().get_result(&sessions);
+        let positions: Vec<usize> = result.duels.iter().map(|duel| duel.position).collect();
+        assert_eq!(vec![1, 2, 3], positions);
+        assert_eq!(Some(Side::Home), result.duels[0].winner);
+        assert_eq!(Some(Side::Away), result.duels[1].winner);
+        // Away shooter without a session
+        assert_eq!(Some(Side::Home), result.duels[2].winner);
+        assert_eq!(None, result.duels[2].away_session_id);
+
+        assert_eq!((2, 1), (result.home_points, result.away_points));
+        assert_eq!((2, 0), (result.home_team_points, result.away_team_points));
+        assert_eq!(result.home_total, sessions.iter().step_by(2).filter_map(|session| session.get_result()).sum());
+    }
+
+    #[test]
+    fn test_draw() {
+        let sessions = vec![session("a", "1", 0), session("b", "2", 0)];
+        let mut league_match = league_match();
+        league_match.pairings.truncate(2);
+        let result = league_match.get_result(&sessions);
+        assert_eq!(None, result.duels[0].winner);
+        assert_eq!((0, 0), (result.home_points, result.away_points));
+        assert_eq!((1, 1), (result.home_team_points, result.away_team_points));
+    }
+
+    #[test]
+    fn test_not_shot() {
+        // No pairing has a session, the match is no draw
+        let result = league_match().get_result(&[session("a", "7", 0)]);
+        assert!(!result.shot);
+        assert_eq!(None, result.duels[0].winner);
+        assert_eq!((0, 0), (result.home_team_points, result.away_team_points));
+
+        let result = league_match().get_result(&[session("a", "1", 0)]);
+        assert!(result.shot);
+        assert_eq!((2, 0), (result.home_team_points, result.away_team_points));
+    }
+}
@@ -0,0 +1,185 @@
+use std::cmp::Ordering;
+
+use super::Shot;
+
+
+
+/// State of a lot test part. The shots are grouped into groups of a fixed size, each group
+/// contains only shots of one ammunition lot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LotTestState {
+    pub group_size: usize,
+    pub groups: Vec<LotGroup>,
+}
+
+/// Group of shots of one lot
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LotGroup {
+    pub lot: String,
+    /// Coordinates of the shots in 1/1000 mm
+    pub shots: Vec<(i32, i32)>,
+    pub stats: GroupStats,
+}
+
+/// Statistics of a group, all values in mm
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GroupStats {
+    /// Largest distance between the centres of two shots
+    pub extreme_spread: f64,
+    /// Average distance of the shots to the mean point of impact
+    pub mean_radius: f64,
+    /// Offset of the mean point of impact from the centre of the target
+    pub mpi_x: f64,
+    pub mpi_y: f64,
+}
+
+/// Result of a lot, averaged over its complete groups
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LotRank {
+    pub lot: String,
+    pub rank: usize,
+    pub number_of_groups: usize,
+    pub extreme_spread: Option<f64>,
+    pub mean_radius: Option<f64>,
+}
+
+impl LotTestState {
+    pub fn new(group_size: usize) -> LotTestState {
+        LotTestState { group_size, groups: vec![] }
+    }
+
+    /// Add a shot to the open group of the given lot, or start a new group
+    /// shot:   shot to add
+    /// lot:    ammunition lot used for the shot
+    pub fn add_shot(&mut self, shot: &Shot, lot: &str) {
+        let group_size = self.group_size;
+        let index = match self.groups.iter().position(|group| group.lot == lot && group.shots.len() < group_size) {
+            Some(index) => index,
+            None => {
+                self.groups.push(LotGroup { lot: lot.to_string(), shots: vec![], stats: GroupStats::default() });
+                self.groups.len() - 1
+            },
+        };
+        let group = &mut self.groups[index];
+        group.shots.push((shot.x, shot.y));
+        group.stats = GroupStats::new(&group.shots);
+    }
+
+    /// Rank the lots by the average mean radius of their complete groups, lots without a
+    /// complete group are ranked last.
+    pub fn get_ranking(&self) -> Vec<LotRank> {
+        let mut lots: Vec<&str> = vec![];
+        for group in &self.groups {
+            if !lots.contains(&group.lot.as_str()) {
+                lots.push(&group.lot);
+            }
+        }
+
+        let mut ranking: Vec<LotRank> = lots.into_iter()
+            .map(|lot| {
+                let complete: Vec<&GroupStats> = self.groups.iter()
+                    .filter(|group| group.lot == lot && group.shots.len() >= self.group_size)
+                    .map(|group| &group.stats)
+                    .collect();
+                let average = |value: fn(&GroupStats) -> f64| if complete.is_empty() {
+                    None
+                } else {
+                    Some(complete.iter().map(|stats| value(stats)).sum::<f64>() / complete.len() as f64)
+                };
+                LotRank {
+                    lot: lot.to_string(),
+                    rank: 0,
+                    number_of_groups: complete.len(),
+                    extreme_spread: average(|stats| stats.extreme_spread),
+                    mean_radius: average(|stats| stats.mean_radius),
+                }
+            })
+            .collect();
+
+        ranking.sort_by(|a, b| match (a.mean_radius, b.mean_radius) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        for (i, rank) in ranking.iter_mut().enumerate() {
+            rank.rank = i + 1;
+        }
+        ranking
+    }
+}
+
+impl GroupStats {
+    /// Calculate the statistics of the given shot coordinates (in 1/1000 mm)
+    pub fn new(shots: &[(i32, i32)]) -> GroupStats {
+        if shots.is_empty() {
+            return GroupStats::default();
+        }
+        let points: Vec<(f64, f64)> = shots.iter()
+            .map(|&(x, y)| (f64::from(x) / 1000_f64, f64::from(y) / 1000_f64))
+            .collect();
+        let distance = |a: (f64, f64), b: (f64, f64)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+
+        let number_of_shots = points.len() as f64;
+        let mpi = (
+            points.iter().map(|point| point.0).sum::<f64>() / number_of_shots,
+            points.iter().map(|point| point.1).sum::<f64>() / number_of_shots,
+        );
+
+        let mut extreme_spread = 0_f64;
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i+1..] {
+                extreme_spread = extreme_spread.max(distance(*a, *b));
+            }
+        }
+
+        GroupStats {
+            extreme_spread,
+            mean_radius: points.iter().map(|point| distance(*point, mpi)).sum::<f64>() / number_of_shots,
+            mpi_x: mpi.0,
+            mpi_y: mpi.1,
+        }
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use session::lot_test::*;
+    use session::{Shot, CountMode};
+    use helper;
+
+    #[test]
+    fn test_group_stats() {
+        let stats = GroupStats::new(&[(0, 0), (3000, 0), (3000, 4000), (0, 4000)]);
+        assert_eq!(5_f64, stats.extreme_spread);
+        assert_eq!(2.5_f64, stats.mean_radius);
+        assert_eq!(1.5_f64, stats.mpi_x);
+        assert_eq!(2_f64, stats.mpi_y);
+    }
+
+    #[test]
+    fn test_ranking() {
+        let target = helper::dsc_demo::lg_target();
+        let shot = |x, y| Shot::from_cartesian_coordinates(x, y, &target, &CountMode::Tenth);
+        let mut state = LotTestState::new(2);
+        state.add_shot(&shot(0, 0), "A");
+        state.add_shot(&shot(0, 0), "B");
+        state.add_shot(&shot(4000, 0), "A");
+        state.add_shot(&shot(1000, 0), "B");
+        state.add_shot(&shot(0, 0), "C");
+
+        assert_eq!(3, state.groups.len());
+        assert_eq!(4_f64, state.groups[0].stats.extreme_spread);
+
+        let ranking = state.get_ranking();
+        let lots: Vec<&str> = ranking.iter().map(|rank| rank.lot.as_str()).collect();
+        assert_eq!(vec!["B", "A", "C"], lots);
+        assert_eq!(Some(0.5_f64), ranking[0].mean_radius);
+        assert_eq!(None, ranking[2].mean_radius);
+        assert_eq!(3, ranking[2].rank);
+    }
+}
@@ -1,13 +1,46 @@
 pub mod counter;
+pub mod event;
+pub mod face_statistics;
+pub mod final_mode;
+pub mod finalization;
+pub mod game;
 pub mod info;
+pub mod league_match;
+pub mod lot_test;
 pub mod part;
+pub mod penalty;
+pub mod rescore;
+pub mod roster;
+pub mod run_analysis;
+pub mod score;
 pub mod series;
 pub mod session;
+pub mod shooter_state;
 pub mod shot;
+pub mod team_event;
+pub mod sight_correction;
+pub mod timer;
+pub mod timing;
 
 pub use self::counter::{Counter, CountMode};
-pub use self::info::{Line, Info};
+pub use self::face_statistics::FaceStatistics;
+pub use self::game::GameState;
+pub use self::info::{Line, Info, User, Club, Team, Equipment};
 pub use self::part::{Part, PartType};
+pub use self::penalty::Penalty;
+pub use self::rescore::{RescoreReport, RescoreNote, rescore};
+pub use self::roster::Roster;
+pub use self::run_analysis::RunAnalysis;
+pub use self::score::Score;
 pub use self::series::Series;
 pub use self::session::{Session, ActivePart, Update};
+pub use self::shooter_state::ShooterState;
 pub use self::shot::{Shot, ShotRaw, AddShotRaw, AddShot};
+pub use self::sight_correction::{SightCorrection, SightScope};
+pub use self::timer::{Timer, Pause};
+pub use self::timing::TimingAnalysis;
+pub use self::final_mode::{FinalState, FinalRank, rank_finals};
+pub use self::lot_test::{LotTestState, LotRank};
+pub use self::finalization::{Finalization, verify_chain};
+pub use self::team_event::{TeamEvent, TeamStanding, rank_teams};
+pub use self::league_match::{LeagueMatch, LeagueMatchResult};
@@ -1,7 +1,10 @@
 use std::time::SystemTime;
+use std::cmp::Ordering;
+use std::mem;
 
 use helper::round_to_one::RoundToOne;
-use super::{Counter, CountMode};
+use super::{Counter, CountMode, Score, FinalState, LotTestState, Penalty, Pause};
+use super::penalty::apply_penalties;
 use super::shot::*;
 use super::series::*;
 use discipline::*;
@@ -14,29 +17,53 @@ pub struct Part {
     pub series: Vec<Series>,
     pub part_type: PartType,
     sum: Counter,
+    /// Sum after the penalties were deducted
+    #[serde(default="Counter::empty")]
+    net_sum: Counter,
+    /// Penalties imposed by a range officer
+    #[serde(default)]
+    pub penalties: Vec<Penalty>,
     number_of_shots: i32,
     result_prediction: Option<String>,
     average: Option<String>,
     date: Option<SystemTime>,
+    /// Stages, timer and score progression, if the part is a final
+    pub final_state: Option<FinalState>,
+    /// Groups of the lots, if the part is a lot test
+    #[serde(default)]
+    pub lot_test: Option<LotTestState>,
 }
 
 pub type PartType = String;
 
 impl Part {
     /// New empty part
-    pub fn new(part_type: PartType) -> Part {
-        let date = None; // TODO
+    /// discipline_part:    DisciplinePart to create the part for
+    /// date:               date the part was created, used to start the timers
+    pub fn new(discipline_part: &DisciplinePart, date: SystemTime) -> Part {
+        let final_state = match discipline_part.mode {
+            PartMode::Final { ref stages } => Some(FinalState::new(stages, date)),
+            PartMode::Default | PartMode::LotTest { .. } => None,
+        };
+        let lot_test = match discipline_part.mode {
+            PartMode::LotTest { group_size } => Some(LotTestState::new(group_size)),
+            PartMode::Default | PartMode::Final { .. } => None,
+        };
 
         Part {
             series: vec![
                 Series::new(),
             ],
-            part_type,
+            part_type: discipline_part.id.clone(),
             sum: Counter::empty(),
+            net_sum: Counter::empty(),
+            penalties: vec![],
             number_of_shots: 0,
             result_prediction: None,
             average: None,
-            date,
+            date: Some(date),
+            final_state,
+            lot_test,
         }
     }
 
@@ -46,6 +73,11 @@ impl Part {
         self.series.push(new_series);
     }
 
+    /// Return the date the part was created
+    pub fn get_date(&self) -> Option<SystemTime> {
+        self.date
+    }
+
     /// Return the current DisciplinePart from the given Discipline
     pub fn get_discipline_part<'a>(&self, discipline: &'a Discipline) -> Option<&'a DisciplinePart> {
         for part in &discipline.parts {
@@ -55,6 +87,224 @@ impl Part {
         }
         return None
     }
+
+    /// Check if shots are counted in this part, shots in the sighting period of a final are not
+    pub fn is_counting(&self) -> bool {
+        match self.final_state {
+            Some(ref final_state) => final_state.is_counting(),
+            None => true,
+        }
+    }
+
+    /// Check if a shot at the given date is late, e.g. after the time of a series of a final
+    /// now:        date of the shot
+    /// pauses:     pauses of the session, the timers are stopped during the pauses
+    pub fn is_late(&self, now: SystemTime, pauses: &[Pause]) -> bool {
+        self.final_state.as_ref().map_or(false, |final_state| final_state.is_late(now, pauses))
+    }
+
+    /// Add a shot which is not counted (e.g. sighting shots of a final)
+    pub fn add_uncounted_shot(&mut self, shot: Shot) {
+        match self.final_state {
+            Some(ref mut final_state) => final_state.add_uncounted_shot(shot),
+            None => println!("ERROR - part does not support uncounted shots"),
+        }
+    }
+
+    /// Add the shot to the group of the given lot, if the part is a lot test
+    /// shot:   shot to add, it has to be added to the series as well
+    /// lot:    ammunition lot used for the shot
+    pub fn add_lot_test_shot(&mut self, shot: &Shot, lot: &str) {
+        if let Some(ref mut lot_test) = self.lot_test {
+            lot_test.add_shot(shot, lot);
+        }
+    }
+
+    /// Check if shots can be moved from or to this part. Shots of finals and lot tests depend on
+    /// their stages or lots, so they can not be moved.
+    pub fn can_move_shots(&self) -> bool {
+        self.final_state.is_none() && self.lot_test.is_none()
+    }
+
+    /// Apply the rule of one hit per face and series, if the discipline part has it. A shot on a
+    /// face, which was already hit in the current series, is annulled and scored as a miss.
+    /// shot:               shot to add to the part next
+    /// discipline:         discipline of the session
+    /// discipline_part:    discipline part of this part
+    fn apply_face_rule(&self, shot: &mut Shot, discipline: &Discipline, discipline_part: &DisciplinePart) {
+        if !discipline_part.one_hit_per_face {
+            return;
+        }
+        let face_hit = match self.series.last() {
+            Some(series) if !series.is_full(discipline_part) => series.shots.iter()
+                .any(|other| other.face == shot.face && !other.annulled),
+            _ => false,
+        };
+        if face_hit {
+            shot.annul(&discipline.target, &discipline_part.count_mode);
+        }
+    }
+
+    /// Update the timers of the part, e.g. end the sighting period of a final
+    /// discipline: discipline of the session
+    /// now:        current date
+    /// pauses:     pauses of the session, the timers are stopped during the pauses
+    /// return:     true if the state of the part changed
+    pub fn update_timers(&mut self, discipline: &Discipline, now: SystemTime, pauses: &[Pause]) -> bool {
+        match (self.get_discipline_part(discipline), self.final_state.as_mut()) {
+            (Some(&DisciplinePart { mode: PartMode::Final { ref stages }, .. }), Some(final_state)) =>
+                final_state.update(stages, now, pauses),
+            _ => false,
+        }
+    }
+
+    /// End the sighting period of the active stage, if the part is a final
+    /// return:     false if the part is no final
+    pub fn start_final_stage(&mut self, discipline: &Discipline, now: SystemTime) -> bool {
+        match (self.get_discipline_part(discipline), self.final_state.as_mut()) {
+            (Some(&DisciplinePart { mode: PartMode::Final { ref stages }, .. }), Some(final_state)) => {
+                final_state.start_stage(stages, now);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Remove all counted shots from the part and reset its series and counters
+    /// return:     the removed shots, in the order they were added
+    pub fn take_shots(&mut self) -> Vec<Shot> {
+        let series = mem::replace(&mut self.series, vec![Series::new()]);
+        self.sum = Counter::empty();
+        self.net_sum = Counter::empty();
+        self.number_of_shots = 0;
+        self.result_prediction = None;
+        self.average = None;
+        series.into_iter().flat_map(|series| series.shots.into_iter()).collect()
+    }
+
+    /// Replace the counted shots of the part. The shots are counted again with the count mode of
+    /// the part, renumbered and grouped into series.
+    /// shots:          new shots of the part, in the order they should be added
+    /// discipline:     discipline of the session
+    pub fn set_shots(&mut self, shots: Vec<Shot>, discipline: &Discipline) {
+        let count_mode = match self.get_discipline_part(discipline) {
+            Some(discipline_part) => discipline_part.count_mode,
+            None => return println!("ERROR - discipline_part not found."),
+        };
+        self.take_shots();
+        for old_shot in shots {
+            let raw = ShotRaw { x: old_shot.x, y: old_shot.y };
+            let mut shot = Shot::from_raw(raw, old_shot.get_date(), &discipline.target, &count_mode);
+            shot.id = old_shot.id;
+            shot.run = old_shot.run;
+            shot.face = old_shot.face;
+            shot.late = old_shot.late;
+            self.add_shot(shot, discipline, &count_mode);
+        }
+        self.update_net_sum(&count_mode);
+    }
+
+    /// Add a penalty to the part and deduct it from the net sum
+    /// penalty:        penalty to add
+    /// count_mode:     count mode of the part
+    pub fn add_penalty(&mut self, penalty: Penalty, count_mode: &CountMode) {
+        self.penalties.push(penalty);
+        self.update_net_sum(count_mode);
+    }
+
+    /// Calculate the net sum from the sum and the penalties of the part
+    fn update_net_sum(&mut self, count_mode: &CountMode) {
+        let value = apply_penalties(self.sum.value, &self.penalties, count_mode);
+        self.net_sum = Counter::new(value, count_mode);
+    }
+
+    /// Return the sum of all counted shots of the part
+    pub fn get_sum(&self) -> Score {
+        self.sum.value
+    }
+
+    /// Return the sum of the part after the penalties were deducted
+    pub fn get_net_sum(&self) -> Score {
+        self.net_sum.value
+    }
+
+    /// Return the result of the part, the net sum, None if the part has no counted shot
+    pub fn get_result(&self) -> Option<Score> {
+        match self.sum.is_empty() {
+            true => None,
+            false => Some(self.net_sum.value),
+        }
+    }
+
+    /// Compare the result of this part with another part, penalties are deducted. A part without
+    /// counted shots is ranked last.
+    /// other:          part to compare with
+    /// count_mode:     count mode the parts were counted with
+    /// return:         Ordering::Less if this part is the better one
+    pub fn compare(&self, other: &Part, count_mode: &CountMode) -> Ordering {
+        count_mode.compare_results(self.get_result(), other.get_result())
+    }
+
+    /// Compare the result of this part with another part, equal results are ranked by the given
+    /// tie break rules, in order until one of them decides the ranking.
+    /// other:          part to compare with
+    /// count_mode:     count mode the parts were counted with
+    /// rules:          tie break rules of the discipline
+    /// return:         Ordering::Less if this part is the better one
+    pub fn compare_by(&self, other: &Part, count_mode: &CountMode, rules: &[TieBreak]) -> Ordering {
+        let ordering = self.compare(other, count_mode);
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        for rule in rules {
+            let ordering = match *rule {
+                TieBreak::Total => self.compare(other, count_mode),
+                // More inner tens are better
+                TieBreak::InnerTens => other.get_inner_tens().cmp(&self.get_inner_tens()),
+                TieBreak::LastSeries => self.series.iter().rev()
+                    .zip(other.series.iter().rev())
+                    .map(|(a, b)| count_mode.compare(a.get_sum(), b.get_sum()))
+                    .find(|ordering| *ordering != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal),
+                // A part without shots has no teiler and is ranked last
+                TieBreak::BestTeiler => match (self.get_best_teiler(), other.get_best_teiler()) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// Return the number of inner tens of all counted shots, annulled shots are misses
+    fn get_inner_tens(&self) -> usize {
+        self.series.iter()
+            .flat_map(|series| series.shots.iter())
+            .filter(|shot| shot.is_inner_ten && !shot.annulled)
+            .count()
+    }
+
+    /// Return the lowest teiler of all counted shots, None if there is no shot which was not
+    /// annulled
+    fn get_best_teiler(&self) -> Option<Score> {
+        self.series.iter()
+            .flat_map(|series| series.shots.iter())
+            .filter(|shot| !shot.annulled)
+            .map(|shot| shot.teiler)
+            .min()
+    }
+
+    /// Return the shot which was added last
+    pub fn get_last_shot(&self) -> Option<&Shot> {
+        self.series.iter().rev()
+            .flat_map(|series| series.shots.iter().rev())
+            .next()
+    }
 }
 
 
@@ -66,27 +316,55 @@ impl AddShot for Part {
     fn add_shot(&mut self, mut shot: Shot, discipline: &Discipline, count_mode: &CountMode) {
         match self.get_discipline_part(discipline) {
             Some(discipline_part) => {
+                self.apply_face_rule(&mut shot, discipline, discipline_part);
+                if shot.late {
+                    shot.annul(&discipline.target, count_mode);
+                }
+
                 // Add the ring count to the part sum
                 self.sum.add(shot.ring_count, &count_mode);
+                self.update_net_sum(count_mode);
                 self.number_of_shots += 1;
                 shot.number = self.number_of_shots;
+
+                // Shots at a moving target get the run of their position in the part, if the run
+                // was not set by the operator or the device
+                if shot.run.is_none() {
+                    shot.run = discipline_part.get_run(self.number_of_shots - 1);
+                }
                 
                 
                 match discipline_part.average {
+                    // The lowest teiler has no average
+                    PartAverage::Average{ .. } if *count_mode == CountMode::Teiler => {}
                     PartAverage::Average{ number_of_shots } => {
-                        let average_complete = self.sum.value / f64::from(self.number_of_shots);
+                        let average_complete = self.sum.value.to_f64() / f64::from(self.number_of_shots);
                         self.result_prediction = Some(format!("{:.0}", (average_complete * f64::from(number_of_shots)).round()));
                         self.average = Some(format!("{:.1}", average_complete.round_to_one()));
                     }
                     PartAverage::None => {}
                 }
 
-                // Add new series if the current series is full
+                // Add new series if the current series is full, in a final each stage starts
+                // with a new series
                 let mut index = self.series.len()-1;
-                if self.series[index].is_full(discipline_part) {
+                let is_full = match discipline_part.get_final_stage(self.number_of_shots - 1) {
+                    Some((_, stage, first_shot)) => {
+                        let shots_in_stage = self.number_of_shots - 1 - first_shot;
+                        !self.series[index].shots.is_empty() &&
+                            (shots_in_stage == 0 ||
+                             (stage.series_length > 0 && shots_in_stage % stage.series_length == 0))
+                    },
+                    None => self.series[index].is_full(discipline_part),
+                };
+                if is_full {
                     self.new_series();
                 }
 
+                if let (&PartMode::Final { ref stages }, Some(ref mut final_state)) = (&discipline_part.mode, self.final_state.as_mut()) {
+                    final_state.add_counted_shot(stages, self.sum.value, shot.get_date());
+                }
+
                 // add shot to the active series
                 index = self.series.len()-1;
                 self.series[index].add_shot(shot, discipline, count_mode);
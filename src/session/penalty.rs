@@ -0,0 +1,31 @@
+use std::time::SystemTime;
+
+use super::{CountMode, Score};
+
+
+
+/// Deduction of points from a part, e.g. for a late shot or an equipment violation
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Penalty {
+    /// Points to deduct, in the teiler count modes the amount is added to the teiler
+    pub amount: Score,
+    pub reason: String,
+    /// Range officer who imposed the penalty
+    pub officer: String,
+    pub date: SystemTime,
+}
+
+/// Apply the given penalties to a result
+/// value:          raw result
+/// penalties:      penalties to apply
+/// count_mode:     count mode of the result, lower values are better in the teiler modes
+/// return:         net result
+pub fn apply_penalties<'a, I>(value: Score, penalties: I, count_mode: &CountMode) -> Score
+    where I: IntoIterator<Item = &'a Penalty>
+{
+    let amount: Score = penalties.into_iter().map(|penalty| penalty.amount).sum();
+    match count_mode.is_teiler() {
+        true => value + amount,
+        false => value - amount,
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt;
+use std::time::SystemTime;
+
+use discipline::Target;
+use super::{Session, Shot, PartType, Score};
+
+
+
+/// Changed score of one shot after re-scoring
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShotChange {
+    pub part_type: PartType,
+    pub number: i32,
+    pub x: i32,
+    pub y: i32,
+    pub old_ring: Score,
+    pub new_ring: Score,
+    pub old_ring_count: Score,
+    pub new_ring_count: Score,
+    pub old_teiler: Score,
+    pub new_teiler: Score,
+    pub old_is_inner_ten: bool,
+    pub new_is_inner_ten: bool,
+}
+
+/// Changed sum of one part after re-scoring
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartChange {
+    pub part_type: PartType,
+    pub old_sum: Score,
+    pub new_sum: Score,
+}
+
+/// Differences between the stored scores of a session and the scores with another target
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RescoreReport {
+    pub session_id: String,
+    pub old_target: String,
+    pub new_target: String,
+    /// Differences between the old and the new target definition
+    pub target_changes: Vec<String>,
+    /// Results of the session, None without a result
+    pub old_result: Option<Score>,
+    pub new_result: Option<Score>,
+    pub parts: Vec<PartChange>,
+    pub shots: Vec<ShotChange>,
+}
+
+impl RescoreReport {
+    /// True if no shot or sum changed
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty() && self.shots.is_empty()
+    }
+}
+
+impl fmt::Display for RescoreReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Session {} ({} -> {})", self.session_id, self.old_target, self.new_target)?;
+        for change in &self.target_changes {
+            writeln!(f, "  Target {}", change)?;
+        }
+        for shot in &self.shots {
+            writeln!(f, "  {} shot {} ({}, {}): {} -> {}, teiler {} -> {}, inner ten {} -> {}",
+                shot.part_type, shot.number, shot.x, shot.y, shot.old_ring, shot.new_ring,
+                shot.old_teiler, shot.new_teiler, yes_no(shot.old_is_inner_ten), yes_no(shot.new_is_inner_ten))?;
+        }
+        for part in &self.parts {
+            writeln!(f, "  {} sum: {} -> {}", part.part_type, part.old_sum, part.new_sum)?;
+        }
+        write!(f, "  Result: {} -> {}", result_text(self.old_result), result_text(self.new_result))
+    }
+}
+
+fn result_text(result: Option<Score>) -> String {
+    result.map_or_else(|| "-".to_string(), |result| result.to_string())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+
+
+/// Note stored in a session, after it was scored again with another target
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RescoreNote {
+    pub date: SystemTime,
+    pub old_target: String,
+    pub new_target: String,
+    pub target_changes: Vec<String>,
+    /// Results of the session, None without a result
+    pub old_result: Option<Score>,
+    pub new_result: Option<Score>,
+    pub number_of_changed_shots: usize,
+}
+
+impl RescoreNote {
+    /// New note from the report of the re-scoring
+    pub fn new(report: &RescoreReport, date: SystemTime) -> RescoreNote {
+        RescoreNote {
+            date,
+            old_target: report.old_target.clone(),
+            new_target: report.new_target.clone(),
+            target_changes: report.target_changes.clone(),
+            old_result: report.old_result,
+            new_result: report.new_result,
+            number_of_changed_shots: report.shots.len(),
+        }
+    }
+}
+
+
+
+/// Describe the differences of two target definitions, which change the scores of shots
+/// return:     one line per changed value, empty if the scoring is the same
+pub fn describe_target_changes(old: &Target, new: &Target) -> Vec<String> {
+    let mut changes = vec![];
+    if old.bullet_diameter != new.bullet_diameter {
+        changes.push(format!("bullet_diameter: {} -> {}", old.bullet_diameter, new.bullet_diameter));
+    }
+    if old.get_max_ring() != new.get_max_ring() {
+        changes.push(format!("max_ring: {} -> {}", old.get_max_ring(), new.get_max_ring()));
+    }
+    if old.get_teiler_factor() != new.get_teiler_factor() {
+        changes.push(format!("teiler_factor: {} -> {}", old.get_teiler_factor(), new.get_teiler_factor()));
+    }
+    if old.inner_ten != new.inner_ten {
+        changes.push(format!("inner_ten: {} -> {}", old.inner_ten, new.inner_ten));
+    }
+    if old.rings.len() != new.rings.len() {
+        changes.push(format!("number of rings: {} -> {}", old.rings.len(), new.rings.len()));
+    }
+    for (old_ring, new_ring) in old.rings.iter().zip(new.rings.iter()) {
+        if old_ring.value != new_ring.value || old_ring.width != new_ring.width {
+            changes.push(format!("ring {} (width {}) -> ring {} (width {})",
+                old_ring.value, old_ring.width, new_ring.value, new_ring.width));
+        }
+    }
+    changes
+}
+
+
+
+/// Score all shots of a session again with the given target, from their raw coordinates. The
+/// event log is not changed, so the session can always be scored again with the stored target.
+/// session:    session to score, the differences are taken against its parts as given
+/// target:     target to use, e.g. a corrected target definition
+/// return:     re-scored session and the differences to the given session, if anything changed
+///             a note is added to the re-scored session
+pub fn rescore(session: &Session, target: &Target) -> (Session, RescoreReport) {
+    let mut rescored = session.clone();
+    rescored.discipline.target = target.clone();
+    rescored.rebuild();
+
+    let mut parts = vec![];
+    let mut shots = vec![];
+    for (old, new) in session.parts.iter().zip(rescored.parts.iter()) {
+        let old_shots = old.series.iter().flat_map(|series| series.shots.iter());
+        let new_shots = new.series.iter().flat_map(|series| series.shots.iter());
+        for (old_shot, new_shot) in old_shots.zip(new_shots) {
+            if has_changed(old_shot, new_shot) {
+                shots.push(ShotChange {
+                    part_type: new.part_type.clone(),
+                    number: new_shot.number,
+                    x: new_shot.x,
+                    y: new_shot.y,
+                    old_ring: old_shot.ring,
+                    new_ring: new_shot.ring,
+                    old_ring_count: old_shot.ring_count,
+                    new_ring_count: new_shot.ring_count,
+                    old_teiler: old_shot.teiler,
+                    new_teiler: new_shot.teiler,
+                    old_is_inner_ten: old_shot.is_inner_ten,
+                    new_is_inner_ten: new_shot.is_inner_ten,
+                });
+            }
+        }
+        if old.get_net_sum() != new.get_net_sum() {
+            parts.push(PartChange {
+                part_type: new.part_type.clone(),
+                old_sum: old.get_net_sum(),
+                new_sum: new.get_net_sum(),
+            });
+        }
+    }
+
+    let report = RescoreReport {
+        session_id: session.id.clone(),
+        old_target: session.discipline.target.title.clone(),
+        new_target: target.title.clone(),
+        target_changes: describe_target_changes(&session.discipline.target, target),
+        old_result: session.get_result(),
+        new_result: rescored.get_result(),
+        parts,
+        shots,
+    };
+    if !report.is_empty() {
+        rescored.rescores.push(RescoreNote::new(&report, SystemTime::now()));
+    }
+    (rescored, report)
+}
+
+/// True if the score of a shot changed
+fn has_changed(old: &Shot, new: &Shot) -> bool {
+    old.ring != new.ring || old.ring_count != new.ring_count || old.teiler != new.teiler ||
+        old.is_inner_ten != new.is_inner_ten
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use session::rescore::*;
+    use session::{Line, ShotRaw, AddShotRaw};
+    use helper;
+
+    #[test]
+    fn test_rescore() {
+        let mut session = Session::new("0".to_string(), Line::demo(), helper::dsc_demo::lg_discipline());
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 2501, y: 0 });
+
+        let (rescored, report) = rescore(&session, &helper::dsc_demo::lg_target());
+        assert!(report.is_empty());
+        assert!(report.target_changes.is_empty());
+        assert!(rescored.rescores.is_empty());
+
+        // Larger bullet, the second shot touches the ten
+        let mut target = helper::dsc_demo::lg_target();
+        target.bullet_diameter = 4.6;
+        let (rescored, report) = rescore(&session, &target);
+        assert_eq!(1, report.shots.len());
+        assert_eq!(2, report.shots[0].number);
+        assert_eq!(Score::from(9.9), report.shots[0].old_ring);
+        assert_eq!(Score::from(10.0), report.shots[0].new_ring);
+        assert_eq!(report.shots[0].old_teiler, report.shots[0].new_teiler);
+        assert!(!report.shots[0].old_is_inner_ten && !report.shots[0].new_is_inner_ten);
+        assert!(format!("{}", report).contains("9.9 -> 10.0, teiler 250.1 -> 250.1, inner ten no -> no"));
+        assert_eq!(1, report.parts.len());
+        assert_eq!(rescored.get_result(), report.new_result);
+        assert_eq!(session.events.len(), rescored.events.len());
+        assert_eq!(vec!["bullet_diameter: 4.5 -> 4.6".to_string()], report.target_changes);
+
+        // The note is kept when the session is rebuilt from its events
+        let mut rebuilt = rescored.clone();
+        rebuilt.rebuild();
+        assert_eq!(1, rebuilt.rescores.len());
+        assert_eq!(1, rebuilt.rescores[0].number_of_changed_shots);
+        assert_eq!(report.new_result, rebuilt.get_result());
+    }
+
+    #[test]
+    fn test_rescore_teiler() {
+        let mut session = Session::new("0".to_string(), Line::demo(), helper::dsc_demo::lg_discipline());
+        session.add_shot_raw(ShotRaw { x: 1000, y: 0 });
+
+        // Only the stored teiler differs, the ring stays the same
+        session.parts[0].series[0].shots[0].teiler = Score::from(90.0);
+        let (_, report) = rescore(&session, &helper::dsc_demo::lg_target());
+        assert_eq!(1, report.shots.len());
+        assert_eq!(report.shots[0].old_ring, report.shots[0].new_ring);
+        assert_eq!((Score::from(90.0), Score::from(100.0)), (report.shots[0].old_teiler, report.shots[0].new_teiler));
+
+        // Smaller inner ten
+        session.rebuild();
+        let mut target = helper::dsc_demo::lg_target();
+        target.inner_ten = 50;
+        let (_, report) = rescore(&session, &target);
+        assert_eq!(1, report.shots.len());
+        assert!(report.shots[0].old_is_inner_ten);
+        assert!(!report.shots[0].new_is_inner_ten);
+    }
+}
@@ -0,0 +1,172 @@
+use std::error::Error as StdError;
+use std::fmt;
+
+use super::{User, Club, Team};
+use registry::{Registry, Shooter};
+
+
+
+/// Known users, clubs and teams, used to validate the info of a session. The roster is part of
+/// the config and is fixed by the organizer, while the registry is edited by the operators. The
+/// shooters of the registry are known users as well, so both are checked for users. Clubs and
+/// teams are only defined by the roster, shooters of the registry have to use them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Roster {
+    #[serde(default)]
+    pub users: Vec<User>,
+    #[serde(default)]
+    pub clubs: Vec<Club>,
+    #[serde(default)]
+    pub teams: Vec<Team>,
+}
+
+impl Roster {
+    /// Validate the given user. Users without id (e.g. guests) are used as they are, users with
+    /// an id have to be in the roster or in the registry, in this case we return the known entry.
+    /// registry:   registry of the shooters
+    pub fn validate_user(&self, user: User, registry: &Registry) -> Result<User, Error> {
+        if user.id.is_empty() {
+            return Ok(user);
+        }
+        match self.users.iter().find(|known| known.id == user.id) {
+            Some(known) => Ok(known.clone()),
+            None => match registry.get(&user.id) {
+                Some(shooter) => Ok(shooter.get_user()),
+                None => Err(Error::UnknownUser(user.id)),
+            },
+        }
+    }
+
+    /// Validate the given club, see validate_user
+    pub fn validate_club(&self, club: Club) -> Result<Club, Error> {
+        if club.id.is_empty() {
+            return Ok(club);
+        }
+        match self.clubs.iter().find(|known| known.id == club.id) {
+            Some(known) => Ok(known.clone()),
+            None => Err(Error::UnknownClub(club.id)),
+        }
+    }
+
+    /// Validate the given team, see validate_user
+    pub fn validate_team(&self, team: Team) -> Result<Team, Error> {
+        if team.id.is_empty() {
+            return Ok(team);
+        }
+        match self.teams.iter().find(|known| known.id == team.id) {
+            Some(known) => Ok(known.clone()),
+            None => Err(Error::UnknownTeam(team.id)),
+        }
+    }
+
+    /// Validate the club and team of a shooter of the registry, see validate_user
+    pub fn validate_shooter(&self, mut shooter: Shooter) -> Result<Shooter, Error> {
+        shooter.club = self.validate_club(shooter.club)?;
+        shooter.team = self.validate_team(shooter.team)?;
+        Ok(shooter)
+    }
+}
+
+
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownUser(String),
+    UnknownClub(String),
+    UnknownTeam(String),
+}
+
+impl StdError for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::UnknownUser(_) => "Unknown user",
+            Error::UnknownClub(_) => "Unknown club",
+            Error::UnknownTeam(_) => "Unknown team",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnknownUser(ref id) => write!(f, "Unknown user: {}", id),
+            Error::UnknownClub(ref id) => write!(f, "Unknown club: {}", id),
+            Error::UnknownTeam(ref id) => write!(f, "Unknown team: {}", id),
+        }
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use session::roster::*;
+    use registry::{Registry, Shooter};
+
+    fn get_roster() -> Roster {
+        Roster {
+            users: vec![
+                User { first_name: "Max".to_string(), last_name: "Mustermann".to_string(), id: "1".to_string() },
+            ],
+            clubs: vec![
+                Club { name: "SV Musterdorf".to_string(), id: "sv".to_string() },
+            ],
+            teams: vec![],
+        }
+    }
+
+    #[test]
+    fn test_validate_user() {
+        let roster = get_roster();
+        let registry = Registry::default();
+        let user = User { first_name: "".to_string(), last_name: "".to_string(), id: "1".to_string() };
+        let user = roster.validate_user(user, &registry).unwrap();
+        assert_eq!("Max", user.first_name);
+        assert_eq!("Mustermann", user.last_name);
+
+        let guest = roster.validate_user(User::guest(), &registry).unwrap();
+        assert_eq!("Guest", guest.first_name);
+
+        let unknown = User { first_name: "".to_string(), last_name: "".to_string(), id: "2".to_string() };
+        assert!(roster.validate_user(unknown, &registry).is_err());
+    }
+
+    #[test]
+    fn test_validate_shooter() {
+        let roster = get_roster();
+        let mut shooter = Shooter {
+            id: "2".to_string(),
+            first_name: "Erika".to_string(),
+            last_name: "Musterfrau".to_string(),
+            club: Club { name: "".to_string(), id: "sv".to_string() },
+            team: Team::empty(),
+            age_class: "".to_string(),
+            license_number: "".to_string(),
+            default_disciplines: vec![],
+            sight: None,
+        };
+        assert_eq!("SV Musterdorf", roster.validate_shooter(shooter.clone()).unwrap().club.name);
+
+        // Shooters of the registry are known users
+        let mut registry = Registry::default();
+        registry.create(shooter.clone()).unwrap();
+        let user = User { first_name: "".to_string(), last_name: "".to_string(), id: "2".to_string() };
+        assert_eq!("Erika", roster.validate_user(user, &registry).unwrap().first_name);
+
+        // but they can not use clubs or teams which are not in the roster
+        shooter.team = Team { name: "".to_string(), id: "1".to_string() };
+        assert!(roster.validate_shooter(shooter).is_err());
+    }
+
+    #[test]
+    fn test_validate_club_team() {
+        let roster = get_roster();
+        let club = Club { name: "".to_string(), id: "sv".to_string() };
+        assert_eq!("SV Musterdorf", roster.validate_club(club).unwrap().name);
+        let team = Team { name: "".to_string(), id: "1".to_string() };
+        assert!(roster.validate_team(team).is_err());
+        assert!(roster.validate_team(Team::empty()).is_ok());
+    }
+}
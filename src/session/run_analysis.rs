@@ -0,0 +1,103 @@
+use discipline::{RunSpeed, RunDirection};
+use super::{Shot, Counter, CountMode};
+use super::counter::mean_point;
+
+
+
+/// Statistics of the shots of one direction of a moving target
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DirectionStatistics {
+    pub direction: RunDirection,
+    pub number_of_shots: usize,
+    pub sum: Counter,
+    /// Mean point of impact in 1/1000 mm, relative to the centre of the target
+    pub mpi_x: i32,
+    pub mpi_y: i32,
+}
+
+/// Results of the shots at a moving target, split by speed and direction. Shots without a run
+/// are not included.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunAnalysis {
+    pub slow: Counter,
+    pub fast: Counter,
+    pub directions: Vec<DirectionStatistics>,
+}
+
+impl RunAnalysis {
+    /// Analyse the given shots
+    /// shots:          shots of the part
+    /// count_mode:     count mode of the part
+    pub fn new(shots: &[&Shot], count_mode: &CountMode) -> RunAnalysis {
+        let sum = |speed: RunSpeed| {
+            let mut counter = Counter::empty();
+            for shot in shots.iter().filter(|shot| shot.run.map(|run| run.speed) == Some(speed)) {
+                counter.add(shot.ring_count, count_mode);
+            }
+            counter
+        };
+
+        let directions = [RunDirection::Left, RunDirection::Right].iter()
+            .map(|&direction| {
+                let shots: Vec<&&Shot> = shots.iter()
+                    .filter(|shot| shot.run.map(|run| run.direction) == Some(direction))
+                    .collect();
+                let mut sum = Counter::empty();
+                for shot in &shots {
+                    sum.add(shot.ring_count, count_mode);
+                }
+                let (mpi_x, mpi_y) = mean_point(&shots);
+                DirectionStatistics {
+                    direction,
+                    number_of_shots: shots.len(),
+                    sum,
+                    mpi_x,
+                    mpi_y,
+                }
+            })
+            .collect();
+
+        RunAnalysis {
+            slow: sum(RunSpeed::Slow),
+            fast: sum(RunSpeed::Fast),
+            directions,
+        }
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use session::run_analysis::*;
+    use session::Shot;
+    use discipline::Run;
+    use helper;
+
+    #[test]
+    fn test_run_analysis() {
+        let target = helper::dsc_demo::lg_target();
+        let shot = |x, speed, direction| {
+            let mut shot = Shot::from_cartesian_coordinates(x, 1000, &target, &CountMode::Integer);
+            shot.run = Some(Run { speed, direction });
+            shot
+        };
+        let shots = vec![
+            shot(0, RunSpeed::Slow, RunDirection::Left),
+            shot(-3000, RunSpeed::Fast, RunDirection::Left),
+            shot(4000, RunSpeed::Slow, RunDirection::Right),
+            Shot::from_cartesian_coordinates(0, 0, &target, &CountMode::Integer),
+        ];
+        let shots: Vec<&Shot> = shots.iter().collect();
+        let analysis = RunAnalysis::new(&shots, &CountMode::Integer);
+
+        assert_eq!(shots[0].ring_count + shots[2].ring_count, analysis.slow.value);
+        assert_eq!(shots[1].ring_count, analysis.fast.value);
+        assert_eq!(RunDirection::Left, analysis.directions[0].direction);
+        assert_eq!(2, analysis.directions[0].number_of_shots);
+        assert_eq!((-1500, 1000), (analysis.directions[0].mpi_x, analysis.directions[0].mpi_y));
+        assert_eq!((4000, 1000), (analysis.directions[1].mpi_x, analysis.directions[1].mpi_y));
+    }
+}
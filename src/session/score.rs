@@ -0,0 +1,155 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub, Neg};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+
+
+/// Fixed-point value with one decimal, stored as integer tenths. Used for rings, teilers and
+/// sums, so that totals are exact. In JSON the value is written as a decimal number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Score(i64);
+
+impl Score {
+    /// Score of zero
+    pub fn zero() -> Score {
+        Score(0)
+    }
+
+    /// New score from integer tenths, e.g. 103 for 10.3
+    pub fn from_tenths(tenths: i64) -> Score {
+        Score(tenths)
+    }
+
+    /// New score from a whole number, e.g. an integer ring
+    pub fn from_integer(value: i64) -> Score {
+        Score(value * 10)
+    }
+
+    /// Return the value in tenths
+    pub fn tenths(self) -> i64 {
+        self.0
+    }
+
+    /// Return the value as float, only used for output and statistics
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / 10_f64
+    }
+
+    /// Return the score without its decimal, rounded down
+    pub fn floor(self) -> Score {
+        Score(self.0.div_euclid(10) * 10)
+    }
+
+    /// Multiply the score with a factor and round to tenths, e.g. the teiler factor of a target
+    pub fn scale(self, factor: f64) -> Score {
+        Score((self.0 as f64 * factor).round() as i64)
+    }
+
+    /// Format the score without its decimal, e.g. for integer rings
+    pub fn to_integer_string(self) -> String {
+        format!("{}", self.0.div_euclid(10))
+    }
+}
+
+impl From<f64> for Score {
+    /// Round a float to tenths, used for values from the config or the clients
+    fn from(value: f64) -> Score {
+        Score((value * 10_f64).round() as i64)
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        write!(f, "{}{}.{}", sign, self.0.abs() / 10, self.0.abs() % 10)
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+    fn add(self, other: Score) -> Score {
+        Score(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+    fn sub(self, other: Score) -> Score {
+        Score(self.0 - other.0)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+    fn neg(self) -> Score {
+        Score(-self.0)
+    }
+}
+
+impl Sum for Score {
+    fn sum<I: Iterator<Item = Score>>(iter: I) -> Score {
+        iter.fold(Score::zero(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Score> for Score {
+    fn sum<I: Iterator<Item = &'a Score>>(iter: I) -> Score {
+        iter.fold(Score::zero(), |sum, score| sum + *score)
+    }
+}
+
+impl Serialize for Score {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Score {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Score, D::Error> {
+        f64::deserialize(deserializer).map(Score::from)
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use serde_json;
+    use session::score::Score;
+
+    #[test]
+    fn test_exact_sum() {
+        let sum: Score = (0..10_000).map(|_| Score::from(10.4)).sum();
+        assert_eq!(Score::from_tenths(1_040_000), sum);
+        assert_eq!("104000.0", format!("{}", sum));
+
+        let sum: Score = vec![Score::from(0.1); 3].iter().sum();
+        assert_eq!(Score::from(0.3), sum);
+    }
+
+    #[test]
+    fn test_floor_and_format() {
+        assert_eq!(Score::from_integer(10), Score::from(10.9).floor());
+        assert_eq!("10", Score::from(10.9).to_integer_string());
+        assert_eq!("-0.5", format!("{}", Score::from(-0.5)));
+        assert_eq!(Score::from_integer(-1), Score::from(-0.5).floor());
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!("1043.6", serde_json::to_string(&Score::from_tenths(10436)).unwrap());
+        let score: Score = serde_json::from_str("10.3").unwrap();
+        assert_eq!(Score::from_tenths(103), score);
+        let score: Score = serde_json::from_str("10").unwrap();
+        assert_eq!(Score::from_integer(10), score);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Counter, CountMode, Shot, AddShot};
+use super::{Counter, CountMode, Score, Shot, AddShot};
 use discipline::*;
 
 
@@ -28,6 +28,11 @@ impl Series {
     pub fn is_full<'a, 'b>(&'a self, discipline_part: &'b DisciplinePart) -> bool {
         return self.shots.len() as i32 >= discipline_part.series_length
     }
+
+    /// Return the counted sum of the series
+    pub fn get_sum(&self) -> Score {
+        self.sum.value
+    }
 }
 
 
@@ -1,6 +1,17 @@
-use std::time::SystemTime;
+use std::time::{SystemTime, Duration, UNIX_EPOCH};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::mem;
 
-use super::{Counter, Shot, AddShot, ShotRaw, AddShotRaw, Part, PartType, Line, Info};
+use super::{Counter, CountMode, Shot, AddShot, ShotRaw, AddShotRaw, Part, PartType, Line, Info,
+    User, Club, Team, Equipment, TimingAnalysis,
+    SightCorrection, SightScope, LotRank, Finalization, ShooterState, Penalty, Pause, RunAnalysis,
+    FaceStatistics, Score, GameState};
+use super::rescore::RescoreNote;
+use super::event::{Event, LogEntry, get_undone_entries};
+use super::finalization::compute_hash;
+use super::penalty::apply_penalties;
+use registry::Shooter;
 use discipline::*;
 
 
@@ -13,6 +24,7 @@ pub type ActivePart = usize;
 /// - the discipline used
 /// - user info (user, club, team)
 /// - statistics
+/// - the event log, all other values are derived from it
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Session {
     pub id: String,
@@ -21,8 +33,54 @@ pub struct Session {
     pub discipline: Discipline,
     pub info: Info,
     sum: Counter,
+    /// Sum after the penalties of all parts were deducted
+    #[serde(default="Counter::empty")]
+    net_sum: Counter,
     number_of_shots: i32,
     date: Option<SystemTime>,
+    /// True if the session was resumed after a restart
+    #[serde(default)]
+    pub resumed: bool,
+    /// True if the session was finished, finished sessions are not resumed
+    #[serde(default)]
+    pub finished: bool,
+    /// Date the session was created, sessions stored before the event log was introduced get the
+    /// date of their first shot, see Session::migrate_legacy_session
+    #[serde(default="legacy_created")]
+    created: SystemTime,
+    /// Append only log of all events of the session, missing in sessions stored before the log was
+    /// introduced
+    #[serde(default)]
+    pub events: Vec<LogEntry>,
+    /// Sign-off of the result, finalized sessions can not be changed
+    #[serde(default)]
+    pub finalization: Option<Finalization>,
+    /// Number of the active shooter, if several shooters share the line
+    #[serde(default)]
+    pub active_shooter: usize,
+    /// Parts and results of the other shooters of a shared line, ordered by their number. The
+    /// result of the session (compare, finalize, print) is the result of the active shooter, team
+    /// events and league matches find the other shooters with get_session_of_user.
+    #[serde(default)]
+    pub other_shooters: Vec<ShooterState>,
+    /// Intervals in which the time limits were stopped, the last one is open while paused
+    #[serde(default)]
+    pub pauses: Vec<Pause>,
+    /// Run of the moving target for the next shots, set by the operator and reset on a part change
+    #[serde(default)]
+    pub selected_run: Option<Run>,
+    /// Notes about scoring the session again with a corrected target, they are not part of the
+    /// event log. Finalized sessions are not scored again, see rescore_stored_sessions
+    #[serde(default)]
+    pub rescores: Vec<RescoreNote>,
+    /// Training game played with the shots of the session
+    #[serde(default)]
+    pub game: Option<GameState>,
+}
+
+/// Creation date of sessions stored before the event log was introduced, until they are migrated
+fn legacy_created() -> SystemTime {
+    UNIX_EPOCH
 }
 
 impl Session {
@@ -31,25 +89,423 @@ impl Session {
     /// discipline:     Discipline to use
     /// return:         Empty session
     pub fn new(id: String, line: Line, discipline: Discipline) -> Session {
-        let part_type = discipline.parts[0].id.clone();
+        Session::new_at(id, line, discipline, SystemTime::now())
+    }
+
+    /// New session with given discipline, created at the given date
+    fn new_at(id: String, line: Line, discipline: Discipline, created: SystemTime) -> Session {
+        let first_part = Part::new(&discipline.parts[0], created);
 
         let date = match discipline.time {
-            Time::InstantStart { duration } => Some(SystemTime::now()),
+            Time::InstantStart { .. } => Some(created),
             _ => None,
         };
 
         Session {
             id,
             parts: vec![
-                Part::new(part_type),
+                first_part,
             ],
             active_part: 0,
             discipline: discipline,
             info: Info::new(line),
             sum: Counter::empty(),
+            net_sum: Counter::empty(),
             number_of_shots: 0,
             date,
+            resumed: false,
+            finished: false,
+            created,
+            events: vec![],
+            finalization: None,
+            active_shooter: 0,
+            other_shooters: vec![],
+            pauses: vec![],
+            selected_run: None,
+            rescores: vec![],
+            game: None,
+        }
+    }
+
+    /// Rebuild all derived values of the session by replaying the event log. Undone events and
+    /// invalidated shots are skipped.
+    pub fn rebuild(&mut self) {
+        if self.events.is_empty() {
+            self.migrate_legacy_session();
+        }
+        let events = mem::replace(&mut self.events, vec![]);
+        let mut session = Session::new_at(
+            self.id.clone(), self.info.line.clone(), self.discipline.clone(), self.created
+        );
+
+        let undone = get_undone_entries(&events);
+        let invalidated = Session::get_invalidated_shots(&events, &undone);
+        for (i, entry) in events.iter().enumerate() {
+            if undone[i] || invalidated.contains(&i) {
+                continue;
+            }
+            session.apply(&entry.event, entry.date, i);
+        }
+
+        session.events = events;
+        session.rescores = mem::replace(&mut self.rescores, vec![]);
+        *self = session;
+    }
+
+    /// Create the event log of a session stored before the log was introduced from its stored
+    /// parts. Each shot is added again from its raw coordinates, so it is scored with the current
+    /// target. The shots of all parts are added in the order they were shot, with a part change
+    /// between shots of different parts. Each series after the first one of a part was started
+    /// with a new target or because the previous series was full, both become a new target.
+    fn migrate_legacy_session(&mut self) {
+        let mut shots: Vec<(&PartType, bool, &Shot)> = self.parts.iter()
+            .flat_map(|part| part.series.iter()
+                .enumerate()
+                .flat_map(move |(i, series)| series.shots.iter()
+                    .enumerate()
+                    .map(move |(j, shot)| (&part.part_type, i > 0 && j == 0, shot))))
+            .collect();
+        shots.sort_by_key(|&(_, _, shot)| shot.get_date());
+
+        let mut date = match shots.first() {
+            Some(&(_, _, shot)) => shot.get_date(),
+            None => return,
+        };
+        self.created = self.date.unwrap_or(date).min(date);
+
+        let mut events = vec![];
+        let mut active_type = &self.discipline.parts[0].id;
+        for (part_type, new_series, shot) in shots {
+            date = shot.get_date();
+            if part_type != active_type {
+                events.push(LogEntry { date, event: Event::PartChanged { part_type: part_type.clone(), force: true } });
+                active_type = part_type;
+            }
+            if new_series {
+                events.push(LogEntry { date, event: Event::NewTarget });
+            }
+            events.push(LogEntry { date, event: Event::ShotAdded { x: shot.x, y: shot.y } });
+        }
+        if let Some(part) = self.parts.get(self.active_part) {
+            if &part.part_type != active_type {
+                events.push(LogEntry { date, event: Event::PartChanged { part_type: part.part_type.clone(), force: true } });
+            }
+        }
+        self.events = events;
+    }
+
+    /// Return the ids of all invalidated shots
+    /// events:     event log
+    /// undone:     flag for each event, if it was undone
+    fn get_invalidated_shots(events: &[LogEntry], undone: &[bool]) -> HashSet<usize> {
+        events.iter().enumerate()
+            .filter_map(|(i, entry)| match entry.event {
+                Event::ShotInvalidated { shot_id } if !undone[i] => Some(shot_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Add an event to the log and apply it. Events which can not be applied (e.g. a part change
+    /// which is not allowed) are not added to the log.
+    ///
+    /// event:      event to add
+    /// return:     false if the event was rejected, e.g. because the session is finalized
+    fn push_event(&mut self, event: Event) -> bool {
+        self.push_event_at(event, SystemTime::now())
+    }
+
+    /// Add an event with the given date to the log and apply it, see push_event
+    fn push_event_at(&mut self, event: Event, date: SystemTime) -> bool {
+        if self.is_finalized() {
+            return false;
+        }
+
+        let index = self.events.len();
+        let (applied, needs_rebuild) = match event {
+            // These events change values derived from earlier events, so we rebuild the session
+            Event::ShotInvalidated { shot_id } => (self.is_valid_shot(shot_id), true),
+            Event::Undo => (self.can_undo(), true),
+            ref event => (self.apply(event, date, index), false),
+        };
+
+        if applied {
+            self.events.push(LogEntry { date, event });
+            if needs_rebuild {
+                self.rebuild();
+            }
+        }
+        applied
+    }
+
+    /// Apply the given event to the derived values of the session
+    /// event:      event to apply
+    /// date:       date of the event
+    /// index:      index of the event in the log
+    /// return:     false if the event could not be applied
+    fn apply(&mut self, event: &Event, date: SystemTime, index: usize) -> bool {
+        match *event {
+            Event::ShotAdded { x, y } => self.apply_shot(ShotRaw { x, y }, date, index),
+            Event::PartChanged { ref part_type, force } => {
+                let changed = self.apply_part_changed(part_type.clone(), force, date);
+                if changed {
+                    self.selected_run = None;
+                }
+                changed
+            },
+            Event::ActivePartChanged { index, force } => {
+                if index < self.parts.len() && self.can_exit_part(force) {
+                    self.active_part = index;
+                    true
+                }
+                else {
+                    false
+                }
+            },
+            Event::NewTarget => self.apply_new_target(),
+            Event::ShotsMoved { ref shot_ids, to_part } => self.apply_shots_moved(shot_ids, to_part),
+            Event::PenaltyAdded { part, amount, ref reason, ref officer } => {
+                let penalty = Penalty { amount, reason: reason.clone(), officer: officer.clone(), date };
+                self.apply_penalty(part, penalty)
+            },
+            Event::UserAssigned { ref user } => {
+                self.info.user = user.clone();
+                true
+            },
+            Event::ClubAssigned { ref club } => {
+                self.info.club = club.clone();
+                true
+            },
+            Event::TeamAssigned { ref team } => {
+                self.info.team = team.clone();
+                true
+            },
+            Event::ShooterAssigned { ref shooter } => {
+                self.info.user = shooter.get_user();
+                self.info.club = shooter.club.clone();
+                self.info.team = shooter.team.clone();
+                true
+            },
+            Event::ShooterAdded { ref user, ref club, ref team } => {
+                let number = self.other_shooters.len() + 1;
+                let state = ShooterState {
+                    number,
+                    user: user.clone(),
+                    club: club.clone(),
+                    team: team.clone(),
+                    parts: vec![Part::new(&self.discipline.parts[0], date)],
+                    active_part: 0,
+                    sum: Counter::empty(),
+                    number_of_shots: 0,
+                    date: match self.discipline.time {
+                        Time::InstantStart { .. } => Some(date),
+                        _ => None,
+                    },
+                };
+                self.activate_shooter(state);
+                true
+            },
+            Event::ShooterSwitched { shooter } => {
+                match self.other_shooters.iter().position(|state| state.number == shooter) {
+                    Some(index) => {
+                        let state = self.other_shooters.remove(index);
+                        self.activate_shooter(state);
+                        true
+                    },
+                    None => {
+                        println!("Shooter {} not found", shooter);
+                        false
+                    },
+                }
+            },
+            Event::TagSet { ref key, ref value } => {
+                match *value {
+                    Some(ref value) => self.info.tags.insert(key.clone(), value.clone()),
+                    None => self.info.tags.remove(key),
+                };
+                true
+            },
+            Event::EquipmentChanged { ref equipment } => {
+                self.info.equipment = equipment.clone();
+                true
+            },
+            Event::RunSelected { run } => {
+                self.selected_run = run;
+                true
+            },
+            Event::ShotRunChanged { shot_id, run } => {
+                let shot = self.parts.iter_mut()
+                    .flat_map(|part| part.series.iter_mut())
+                    .flat_map(|series| series.shots.iter_mut())
+                    .find(|shot| shot.id == shot_id);
+                match shot {
+                    Some(shot) => {
+                        shot.run = Some(run);
+                        true
+                    },
+                    None => {
+                        println!("Shot {} not found", shot_id);
+                        false
+                    },
+                }
+            },
+            Event::TimerPaused { ref reason } => {
+                if self.is_paused() {
+                    println!("Session is already paused");
+                    return false;
+                }
+                self.pauses.push(Pause { start: date, end: None, reason: reason.clone() });
+                true
+            },
+            Event::TimerResumed => {
+                match self.pauses.last_mut() {
+                    Some(ref mut pause) if pause.end.is_none() => {
+                        pause.end = Some(date);
+                        true
+                    },
+                    _ => {
+                        println!("Session is not paused");
+                        false
+                    },
+                }
+            },
+            Event::FinalStageStarted => {
+                let started = self.parts[self.active_part].start_final_stage(&self.discipline, date);
+                if !started {
+                    println!("Part is no final");
+                }
+                started
+            },
+            Event::GameStarted { ref game } => {
+                self.game = Some(GameState::new(game.clone(), date));
+                true
+            },
+            Event::GameStopped => {
+                match self.game.take() {
+                    Some(_) => true,
+                    None => {
+                        println!("No game running");
+                        false
+                    },
+                }
+            },
+            Event::SessionResumed => {
+                self.resumed = true;
+                true
+            },
+            Event::SessionFinished => {
+                self.finished = true;
+                true
+            },
+            Event::SessionFinalized { ref signed_off_by, ref hash, ref previous_hash } => {
+                self.finalization = Some(Finalization {
+                    signed_off_by: signed_off_by.clone(),
+                    date,
+                    hash: hash.clone(),
+                    previous_hash: previous_hash.clone(),
+                });
+                self.finished = true;
+                true
+            },
+            // Only used when rebuilding the session
+            Event::ShotInvalidated { .. } | Event::Undo => true,
+        }
+    }
+
+    /// Mark the session as resumed after a restart
+    pub fn resume(&mut self) {
+        self.push_event(Event::SessionResumed);
+    }
+
+    /// Mark the session as finished, it will not be resumed after a restart
+    pub fn finish(&mut self) {
+        if !self.finished {
+            self.push_event(Event::SessionFinished);
+        }
+    }
+
+    /// Make the given shooter the active one, the state of the current shooter is moved to the
+    /// other shooters.
+    fn activate_shooter(&mut self, state: ShooterState) {
+        let previous = ShooterState {
+            number: self.active_shooter,
+            user: mem::replace(&mut self.info.user, state.user),
+            club: mem::replace(&mut self.info.club, state.club),
+            team: mem::replace(&mut self.info.team, state.team),
+            parts: mem::replace(&mut self.parts, state.parts),
+            active_part: mem::replace(&mut self.active_part, state.active_part),
+            sum: mem::replace(&mut self.sum, state.sum),
+            number_of_shots: mem::replace(&mut self.number_of_shots, state.number_of_shots),
+            date: mem::replace(&mut self.date, state.date),
+        };
+        self.active_shooter = state.number;
+        self.other_shooters.push(previous);
+        self.other_shooters.sort_by_key(|state| state.number);
+        self.update_net_sum();
+    }
+
+    /// Return the session as seen by the given user, who is the active shooter or one of the other
+    /// shooters of a shared line. The parts and results of the returned session are the ones of
+    /// this user.
+    /// user_id:    id of the user, users without id are not searched
+    /// return:     None if the user does not shoot in this session
+    pub fn get_session_of_user(&self, user_id: &str) -> Option<Session> {
+        if user_id.is_empty() {
+            return None;
+        }
+        if self.info.user.id == user_id {
+            return Some(self.clone());
+        }
+        let index = self.other_shooters.iter().position(|state| state.user.id == user_id)?;
+        let mut session = self.clone();
+        let state = session.other_shooters.remove(index);
+        session.activate_shooter(state);
+        Some(session)
+    }
+
+    /// Calculate the net sum from the sum and the penalties of all parts
+    fn update_net_sum(&mut self) {
+        let count_mode = self.get_count_mode();
+        let penalties = self.parts.iter().flat_map(|part| part.penalties.iter());
+        let value = apply_penalties(self.sum.value, penalties, &count_mode);
+        self.net_sum = Counter::new(value, &count_mode);
+    }
+
+    /// Sign off the result and lock the session against further changes, the signed off result is
+    /// the result of the active shooter
+    /// signed_off_by:  name of the person who signed off the result
+    /// previous_hash:  hash of the previous finalized session of the line
+    /// return:         false if the session was already finalized
+    pub fn finalize(&mut self, signed_off_by: String, previous_hash: Option<String>) -> bool {
+        if self.is_finalized() {
+            return false;
         }
+        let date = SystemTime::now();
+        let hash = compute_hash(self, &self.events, &signed_off_by, date, &previous_hash);
+        self.push_event_at(Event::SessionFinalized { signed_off_by, hash, previous_hash }, date)
+    }
+
+    /// True if the session was signed off, all further events are rejected
+    pub fn is_finalized(&self) -> bool {
+        self.finalization.is_some()
+    }
+
+    /// Check if the shot with the given id exists and is not invalidated yet
+    fn is_valid_shot(&self, shot_id: usize) -> bool {
+        match self.events.get(shot_id) {
+            Some(&LogEntry { event: Event::ShotAdded { .. }, .. }) => {
+                let undone = get_undone_entries(&self.events);
+                !undone[shot_id] && !Session::get_invalidated_shots(&self.events, &undone).contains(&shot_id)
+            },
+            _ => false,
+        }
+    }
+
+    /// Check if there is an operator action which can be undone
+    fn can_undo(&self) -> bool {
+        let undone = get_undone_entries(&self.events);
+        self.events.iter().zip(undone.iter())
+            .any(|(entry, undone)| !undone && entry.event.is_operator_action())
     }
 
     /// Return the active part
@@ -64,6 +520,156 @@ impl Session {
         self.discipline.get_part_from_type(active_part_type)
     }
 
+    /// Return the first part in final mode, together with its DisciplinePart
+    pub fn get_final_part(&self) -> Option<(&Part, &DisciplinePart)> {
+        for part in &self.parts {
+            if let Some(discipline_part) = part.get_discipline_part(&self.discipline) {
+                if let PartMode::Final { .. } = discipline_part.mode {
+                    return Some((part, discipline_part));
+                }
+            }
+        }
+        None
+    }
+
+    /// Update the timers of the active part (e.g. the sighting period of a final), the timers are
+    /// stopped while the session is paused
+    /// return:     true if the session changed
+    pub fn update_timers(&mut self) -> bool {
+        self.parts[self.active_part].update_timers(&self.discipline, SystemTime::now(), &self.pauses)
+    }
+
+    /// Return the count mode of the sessions result, which is the count mode of the main part
+    pub fn get_count_mode(&self) -> CountMode {
+        match self.discipline.get_main_part() {
+            Some(discipline_part) => discipline_part.count_mode,
+            None => CountMode::Integer,
+        }
+    }
+
+    /// Return the end of the time limit of the active part, or of the session if the part has no
+    /// time limit. Durations are given in minutes, pauses after the start extend the time limit.
+    /// now:    current date, open pauses last until now
+    pub fn get_time_limit_end(&self, now: SystemTime) -> Option<SystemTime> {
+        let part = self.get_active_part();
+        let part_limit = match self.get_active_discipline_part().map(|part| part.time) {
+            Some(Time::InstantStart { duration }) => part.get_date().map(|date| (date, duration)),
+            Some(Time::FirstShot { duration }) => part.series.first()
+                .and_then(|series| series.shots.first())
+                .map(|shot| (shot.get_date(), duration)),
+            _ => None,
+        };
+        let limit = part_limit.or_else(|| match self.discipline.time {
+            Time::InstantStart { duration } | Time::FirstShot { duration } =>
+                self.date.map(|date| (date, duration)),
+            Time::None => None,
+        });
+        limit.map(|(start, duration)| {
+            let paused = self.pauses.iter()
+                .fold(Duration::from_secs(0), |sum, pause| sum + pause.duration_after(start, now));
+            start + Duration::from_secs(duration.max(0) as u64 * 60) + paused
+        })
+    }
+
+    /// Check if the time limits of the session are paused
+    pub fn is_paused(&self) -> bool {
+        self.pauses.last().map_or(false, |pause| pause.end.is_none())
+    }
+
+    /// Return the timing analysis of all counted shots
+    /// now:    date to calculate the remaining time of the active part for
+    pub fn get_timing_analysis(&self, now: SystemTime) -> TimingAnalysis {
+        let part = self.get_active_part();
+        let remaining_shots = self.get_active_discipline_part()
+            .and_then(|discipline_part| discipline_part.number_of_shots)
+            .map(|number_of_shots| {
+                let shots = part.series.iter().map(|series| series.shots.len() as i32).sum::<i32>();
+                (number_of_shots - shots).max(0)
+            });
+        TimingAnalysis::new(&self.parts, self.get_time_limit_end(now), remaining_shots, now)
+    }
+
+    /// Return the sight correction for the shots of the current series or the active part
+    /// scope:      shots to use for the mean point of impact
+    /// sight:      sight to calculate the clicks for
+    pub fn get_sight_correction(&self, scope: SightScope, sight: &Sight) -> Option<SightCorrection> {
+        let part = self.get_active_part();
+        let shots: Vec<&Shot> = match scope {
+            SightScope::Series => match part.series.last() {
+                Some(series) => series.shots.iter().collect(),
+                None => vec![],
+            },
+            SightScope::Part => part.series.iter().flat_map(|series| series.shots.iter()).collect(),
+        };
+        SightCorrection::new(&shots, sight)
+    }
+
+    /// Return the results of the active part split by the runs of the moving target
+    pub fn get_run_analysis(&self) -> RunAnalysis {
+        let part = self.get_active_part();
+        let shots: Vec<&Shot> = part.series.iter().flat_map(|series| series.shots.iter()).collect();
+        let count_mode = match self.get_active_discipline_part() {
+            Some(discipline_part) => discipline_part.count_mode,
+            None => self.get_count_mode(),
+        };
+        RunAnalysis::new(&shots, &count_mode)
+    }
+
+    /// Return the statistics of each face of the lane for the active part
+    pub fn get_face_statistics(&self) -> Vec<FaceStatistics> {
+        let part = self.get_active_part();
+        let shots: Vec<&Shot> = part.series.iter().flat_map(|series| series.shots.iter()).collect();
+        let count_mode = match self.get_active_discipline_part() {
+            Some(discipline_part) => discipline_part.count_mode,
+            None => self.get_count_mode(),
+        };
+        FaceStatistics::new(&shots, &self.discipline.faces, &count_mode)
+    }
+
+    /// Return the ranking of the lots of the first lot test part, None if there is no lot test
+    pub fn get_lot_ranking(&self) -> Option<Vec<LotRank>> {
+        self.parts.iter()
+            .filter_map(|part| part.lot_test.as_ref())
+            .next()
+            .map(|lot_test| lot_test.get_ranking())
+    }
+
+    /// Return the date the session was created
+    pub fn get_created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// Return the part with the result of the session, the last part of the main discipline part
+    pub fn get_result_part(&self) -> Option<&Part> {
+        match self.discipline.get_main_part() {
+            Some(discipline_part) => self.parts.iter().rev()
+                .find(|part| part.part_type == discipline_part.id),
+            None => None,
+        }
+    }
+
+    /// Return the result of the session, the net sum of the result part. None if the session has
+    /// no result part or no counted shot in it. The sums of all parts are no result, since the
+    /// parts can be counted with different count modes.
+    pub fn get_result(&self) -> Option<Score> {
+        self.get_result_part().and_then(|part| part.get_result())
+    }
+
+    /// Compare the result of this session with another session, lower teilers are better in the
+    /// teiler count modes, higher rings otherwise. Equal results are ranked by the tie break rules
+    /// of the discipline, a session without a result is ranked last. Only the results of the
+    /// active shooters are compared, both sessions have to be of the same discipline.
+    /// other:      session to compare with
+    /// return:     Ordering::Less if this session is the better one
+    pub fn compare(&self, other: &Session) -> Ordering {
+        let count_mode = self.get_count_mode();
+        match (self.get_result_part(), other.get_result_part()) {
+            (Some(part), Some(other_part)) =>
+                part.compare_by(other_part, &count_mode, &self.discipline.tie_break),
+            _ => count_mode.compare_results(self.get_result(), other.get_result()),
+        }
+    }
+
     /// Check if the user is allowed to exit the current part. If force is true, we can always exit
     ///
     /// force:      allows exit, even if the part does not allow exit
@@ -92,29 +698,178 @@ impl Session {
 
 
 
-impl AddShotRaw for Session {
-    fn add_shot_raw(&mut self, shot_raw: ShotRaw) {
+impl Session {
+    /// Add a new shot to the active part
+    /// shot_raw:   shot from the device
+    /// date:       date of the shot
+    /// shot_id:    index of the event which added the shot
+    fn apply_shot(&mut self, shot_raw: ShotRaw, date: SystemTime, shot_id: usize) -> bool {
         match self.get_active_discipline_part() {
             Some(discipline_part) => {
                 self.date = match self.discipline.time {
-                    Time::FirstShot { duration } if self.number_of_shots == 0 => Some(SystemTime::now()),
+                    Time::FirstShot { .. } if self.number_of_shots == 0 => Some(date),
                     _ => self.date,
                 };
 
                 // TODO check time limit
+                self.parts[self.active_part].update_timers(&self.discipline, date, &self.pauses);
 
+                // On lanes with several faces, the shot is scored on the face it hit
                 let count_mode = discipline_part.count_mode;
-                let shot = Shot::from_raw(shot_raw, &self.discipline.target, &count_mode);
+                let (face, x, y) = self.discipline.get_face(shot_raw.x, shot_raw.y);
+                let mut shot = Shot::from_raw(ShotRaw { x, y }, date, &self.discipline.target, &count_mode);
+                shot.id = shot_id;
+                shot.run = self.selected_run;
+                shot.face = face;
+                shot.late = self.parts[self.active_part].is_late(date, &self.pauses);
+
+                // Shots in the sighting period of a final are not counted
+                if !self.parts[self.active_part].is_counting() {
+                    self.parts[self.active_part].add_uncounted_shot(shot);
+                    return true;
+                }
+
+                // add shot to the active part, which applies the rules of the part (e.g. one
+                // hit per face), and count it as it was added
+                let active_part = &mut self.parts[self.active_part];
+                active_part.add_shot(shot, &self.discipline, &count_mode);
+                let shot = match active_part.get_last_shot() {
+                    Some(shot) => shot.clone(),
+                    None => return false,
+                };
+                let lot = self.info.equipment.ammo_lot.clone().unwrap_or_else(|| "-".to_string());
+                active_part.add_lot_test_shot(&shot, &lot);
 
+                if let Some(ref mut game) = self.game {
+                    game.add_shot(&shot);
+                }
                 self.sum.add(shot.ring_count, &count_mode);
+                self.update_net_sum();
                 self.number_of_shots += 1;
+                true
+            },
+            None => {
+                println!("no discipline_part");
+                false
+            },
+        }
+    }
 
-                // add shot to the active session
+    /// Add a new series to the active part, if the discipline part allows it
+    fn apply_new_target(&mut self) -> bool {
+        if let Some(discipline_part) = self.get_active_discipline_part() {
+            if discipline_part.enable_reset_to_new_target {
                 let active_part = &mut self.parts[self.active_part];
-                active_part.add_shot(shot, &self.discipline, &discipline_part.count_mode);
-            },
-            None => println!("no discipline_part"),
+                active_part.new_series();
+                return true;
+            }
+            else {
+                println!("New target not allowed");
+            }
+        }
+        else {
+            println!("Unkown part");
+        }
+        false
+    }
+
+    /// Move the shots with the given ids to another part. Both parts are counted again. Shots of
+    /// a final or lot test can not be moved, see Part::can_move_shots.
+    fn apply_shots_moved(&mut self, shot_ids: &[usize], to_part: ActivePart) -> bool {
+        if to_part >= self.parts.len() || !self.parts[to_part].can_move_shots() {
+            println!("Can not move shots to part {}", to_part);
+            return false;
+        }
+
+        let mut moved_shots = vec![];
+        for i in 0..self.parts.len() {
+            let part = &self.parts[i];
+            let contains_moved = part.series.iter()
+                .flat_map(|series| series.shots.iter())
+                .any(|shot| shot_ids.contains(&shot.id));
+            if i == to_part || !part.can_move_shots() || !contains_moved {
+                continue;
+            }
+
+            let (moved, kept): (Vec<Shot>, Vec<Shot>) = self.parts[i].take_shots().into_iter()
+                .partition(|shot| shot_ids.contains(&shot.id));
+            self.parts[i].set_shots(kept, &self.discipline);
+            moved_shots.extend(moved);
+        }
+        if moved_shots.is_empty() {
+            println!("No shots to move");
+            return false;
+        }
+
+        let mut shots = self.parts[to_part].take_shots();
+        shots.extend(moved_shots);
+        shots.sort_by_key(|shot| shot.id);
+        self.parts[to_part].set_shots(shots, &self.discipline);
+
+        // The parts can have different count modes, so we count the session sum again
+        self.sum = Counter::empty();
+        for part in &self.parts {
+            if let Some(discipline_part) = part.get_discipline_part(&self.discipline) {
+                for shot in part.series.iter().flat_map(|series| series.shots.iter()) {
+                    self.sum.add(shot.ring_count, &discipline_part.count_mode);
+                }
+            }
+        }
+        self.update_net_sum();
+        true
+    }
+
+    /// Add a penalty to the part with the given index, the amount has to be positive
+    fn apply_penalty(&mut self, part: ActivePart, penalty: Penalty) -> bool {
+        if penalty.amount <= Score::zero() || part >= self.parts.len() {
+            println!("Invalid penalty");
+            return false;
+        }
+        let count_mode = match self.parts[part].get_discipline_part(&self.discipline) {
+            Some(discipline_part) => discipline_part.count_mode,
+            None => return false,
+        };
+        self.parts[part].add_penalty(penalty, &count_mode);
+        self.update_net_sum();
+        true
+    }
+
+    /// Change to the part with the given type, if there is none, we create a new one
+    fn apply_part_changed(&mut self, part_type: PartType, force: bool, date: SystemTime) -> bool {
+        if self.can_exit_part(force) {
+
+            // Search in parts for a part with the given type, if found, we switch to it
+            for (i, part) in self.parts.iter().enumerate() {
+                if part.part_type == part_type {
+                    self.active_part = i;
+                    return true;
+                }
+            }
+
+
+            // Otherwise init a new part
+            if let Some(discipline_part) = self.discipline.get_part_from_type(part_type.clone()) {
+                self.parts.push(Part::new(&discipline_part, date));
+                self.active_part = self.parts.len()-1;
+                return true;
+            }
+            else {
+                println!("Unkown type");
+            }
+
+        }
+        else {
+            println!("Part change not allowed");
         }
+        false
+    }
+}
+
+
+
+impl AddShotRaw for Session {
+    fn add_shot_raw(&mut self, shot_raw: ShotRaw) {
+        self.push_event(Event::ShotAdded { x: shot_raw.x, y: shot_raw.y });
     }
 }
 
@@ -136,20 +891,49 @@ pub trait Update {
 
 
 
-    // Update the user of the current session
-    //
-    // user:    new user
-    // fn set_user(&mut self, user: User);
+    /// Update the user of the current session
+    ///
+    /// user:    new user
+    fn set_user(&mut self, user: User);
 
-    // Update the team of the current session
-    //
-    // team:    new team
-    // fn set_team(&mut self, team: Team);
+    /// Update the team of the current session
+    ///
+    /// team:    new team
+    fn set_team(&mut self, team: Team);
 
-    // Update the club of the current session
-    //
-    // club:    new club
-    // fn set_club(&mut self, club: Club);
+    /// Update the club of the current session
+    ///
+    /// club:    new club
+    fn set_club(&mut self, club: Club);
+
+    /// Set user, club and team of the current session from a shooter of the registry
+    ///
+    /// shooter: shooter to assign
+    fn set_shooter(&mut self, shooter: Shooter);
+
+    /// Add a shooter to the line, who shares the line with the current shooter. The new shooter
+    /// becomes the active one, and gets its own parts and series.
+    ///
+    /// user:    user of the new shooter
+    /// club:    club of the new shooter
+    /// team:    team of the new shooter
+    fn add_shooter(&mut self, user: User, club: Club, team: Team);
+
+    /// Change the active shooter, following shots are attributed to this shooter
+    ///
+    /// shooter: number of the shooter
+    fn switch_shooter(&mut self, shooter: usize);
+
+    /// Set a tag of the current session
+    ///
+    /// key:     key of the tag
+    /// value:   new value, None removes the tag
+    fn set_tag(&mut self, key: String, value: Option<String>);
+
+    /// Update the equipment of the current session
+    ///
+    /// equipment:   new equipment
+    fn set_equipment(&mut self, equipment: Equipment);
 
 
 
@@ -164,63 +948,159 @@ pub trait Update {
     /// index:   Index of the part to change to
     /// force:   Flag if we should check if the discipline allows a part change ot not
     fn set_active_part(&mut self, index: ActivePart, force: bool);
+
+    /// End the sighting period of the active final stage and start counting
+    fn start_final_stage(&mut self);
+
+    /// Invalidate a shot, it will no longer be counted
+    ///
+    /// shot_id:    id of the shot to invalidate
+    fn invalidate_shot(&mut self, shot_id: usize);
+
+    /// Move a range of shots to another part, e.g. match shots fired in the probe part
+    ///
+    /// from_part:  index of the part containing the shots
+    /// first_shot: number of the first shot to move
+    /// last_shot:  number of the last shot to move
+    /// to_part:    index of the part to move the shots to
+    fn move_shots(&mut self, from_part: ActivePart, first_shot: i32, last_shot: i32, to_part: ActivePart);
+
+    /// Deduct points from a part
+    ///
+    /// part:       index of the part
+    /// amount:     points to deduct
+    /// reason:     reason for the penalty, e.g. late shot
+    /// officer:    range officer who imposed the penalty
+    fn add_penalty(&mut self, part: ActivePart, amount: Score, reason: String, officer: String);
+
+    /// Select the run of the moving target for the next shots
+    ///
+    /// run:    run of the next shots until the next part change, None to use the runs of the
+    ///         discipline part
+    fn select_run(&mut self, run: Option<Run>);
+
+    /// Correct the run of a shot at a moving target
+    ///
+    /// shot_id:    id of the shot
+    /// run:        run the shot was fired in
+    fn set_shot_run(&mut self, shot_id: usize, run: Run);
+
+    /// Stop the time limits of the session and the active part
+    ///
+    /// reason:     reason for the pause, e.g. range stop
+    fn pause(&mut self, reason: Option<String>);
+
+    /// Continue the time limits after a pause
+    fn resume_timer(&mut self);
+
+    /// Start a training game, a running game is replaced
+    ///
+    /// game:   definition of the game
+    fn start_game(&mut self, game: Game);
+
+    /// Stop the running game
+    fn stop_game(&mut self);
+
+    /// Undo the last operator action (e.g. part change, new target, invalidated shot)
+    fn undo(&mut self);
 }
 
 impl Update for Session {
 
     fn new_target(&mut self) {
-        // let part_type = self.get_active_part().part_type.clone();
-        // self.set_part(part_type, force);
-        
-        if let Some(discipline_part) = self.get_active_discipline_part() {
-            if discipline_part.enable_reset_to_new_target {
-                let active_part = &mut self.parts[self.active_part];
-                active_part.new_series();
-            }
-            else {
-                println!("New target not allowed");
-            }
-        }
-        else {
-            println!("Unkown part");
-        }
+        self.push_event(Event::NewTarget);
+    }
+
+    fn set_user(&mut self, user: User) {
+        self.push_event(Event::UserAssigned { user });
+    }
+
+    fn set_team(&mut self, team: Team) {
+        self.push_event(Event::TeamAssigned { team });
+    }
+
+    fn set_club(&mut self, club: Club) {
+        self.push_event(Event::ClubAssigned { club });
+    }
+
+    fn set_shooter(&mut self, shooter: Shooter) {
+        self.push_event(Event::ShooterAssigned { shooter });
+    }
+
+    fn add_shooter(&mut self, user: User, club: Club, team: Team) {
+        self.push_event(Event::ShooterAdded { user, club, team });
+    }
+
+    fn switch_shooter(&mut self, shooter: usize) {
+        self.push_event(Event::ShooterSwitched { shooter });
+    }
+
+    fn set_tag(&mut self, key: String, value: Option<String>) {
+        self.push_event(Event::TagSet { key, value });
+    }
+
+    fn set_equipment(&mut self, equipment: Equipment) {
+        self.push_event(Event::EquipmentChanged { equipment });
     }
 
     fn set_part(&mut self, part_type: PartType, force: bool) {
-        if self.can_exit_part(force) {
-            
-            // Search in parts for a part with the given type, if found, we switch to it
-            for (i, part) in self.parts.iter().enumerate() {
-                if part.part_type == part_type {
-                    self.active_part = i;
-                    return
-                }
-            }
-            
-            
-            // Otherwise init a new part
-            if let Some(_) = self.discipline.get_part_from_type(part_type.clone()) {
-                self.parts.push(Part::new(part_type));
-                self.active_part = self.parts.len()-1;
-            }
-            else {
-                println!("Unkown type");
-            }
-            
-        }
-        else {
-            println!("Part change not allowed");
-        }
-        
-        
-        
-        
+        self.push_event(Event::PartChanged { part_type, force });
     }
 
     fn set_active_part(&mut self, index: ActivePart, force: bool) {
-        if index < self.parts.len() && self.can_exit_part(force) {
-            self.active_part = index;
-        }
+        self.push_event(Event::ActivePartChanged { index, force });
+    }
+
+    fn start_final_stage(&mut self) {
+        self.push_event(Event::FinalStageStarted);
+    }
+
+    fn invalidate_shot(&mut self, shot_id: usize) {
+        self.push_event(Event::ShotInvalidated { shot_id });
+    }
+
+    fn move_shots(&mut self, from_part: ActivePart, first_shot: i32, last_shot: i32, to_part: ActivePart) {
+        let shot_ids: Vec<usize> = match self.parts.get(from_part) {
+            Some(part) => part.series.iter()
+                .flat_map(|series| series.shots.iter())
+                .filter(|shot| shot.number >= first_shot && shot.number <= last_shot)
+                .map(|shot| shot.id)
+                .collect(),
+            None => vec![],
+        };
+        self.push_event(Event::ShotsMoved { shot_ids, to_part });
+    }
+
+    fn add_penalty(&mut self, part: ActivePart, amount: Score, reason: String, officer: String) {
+        self.push_event(Event::PenaltyAdded { part, amount, reason, officer });
+    }
+
+    fn select_run(&mut self, run: Option<Run>) {
+        self.push_event(Event::RunSelected { run });
+    }
+
+    fn set_shot_run(&mut self, shot_id: usize, run: Run) {
+        self.push_event(Event::ShotRunChanged { shot_id, run });
+    }
+
+    fn pause(&mut self, reason: Option<String>) {
+        self.push_event(Event::TimerPaused { reason });
+    }
+
+    fn resume_timer(&mut self) {
+        self.push_event(Event::TimerResumed);
+    }
+
+    fn start_game(&mut self, game: Game) {
+        self.push_event(Event::GameStarted { game });
+    }
+
+    fn stop_game(&mut self) {
+        self.push_event(Event::GameStopped);
+    }
+
+    fn undo(&mut self) {
+        self.push_event(Event::Undo);
     }
 
 }
@@ -238,8 +1118,11 @@ mod test {
     use session::shot::*;
     use session::session::*;
     use session::counter::CountMode;
+    use session::Update;
+    use std::cmp::Ordering;
     use discipline::*;
     use helper;
+    use serde_json;
 
     fn get_session() -> Session {
         let discipline = helper::dsc_demo::lg_discipline();
@@ -257,6 +1140,523 @@ mod test {
         assert_eq!(0, session.active_part);
     }
 
+    #[test]
+    fn test_compare_teiler() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].count_mode = CountMode::Teiler;
+        let mut session_a = Session::new("a".to_string(), Line::demo(), discipline.clone());
+        let mut session_b = Session::new("b".to_string(), Line::demo(), discipline);
+
+        session_a.add_shot_raw(ShotRaw { x: 1000, y: 0 });
+        session_a.add_shot_raw(ShotRaw { x: 500, y: 0 });
+        session_b.add_shot_raw(ShotRaw { x: 800, y: 0 });
+
+        assert_eq!(Score::from_integer(50), session_a.sum.value);
+        assert_eq!(Ordering::Less, session_a.compare(&session_b));
+        assert_eq!(Ordering::Greater, session_b.compare(&session_a));
+        assert_eq!(Ordering::Less, session_a.parts[0].compare(&session_b.parts[0], &CountMode::Teiler));
+    }
+
+    #[test]
+    fn test_compare_without_result() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].count_mode = CountMode::Teiler;
+        let mut session_a = Session::new("a".to_string(), Line::demo(), discipline.clone());
+        let session_b = Session::new("b".to_string(), Line::demo(), discipline.clone());
+
+        // A line which never shot does not win with teiler 0
+        session_a.add_shot_raw(ShotRaw { x: 3000, y: 0 });
+        assert_eq!(None, session_b.get_result());
+        assert_eq!(Ordering::Less, session_a.compare(&session_b));
+        assert_eq!(Ordering::Greater, session_b.compare(&session_a));
+
+        // Shots of the probe part are no result of the match part
+        let mut match_part = discipline.parts[0].clone();
+        match_part.id = "match".to_string();
+        match_part.main_part = true;
+        discipline.parts[0].count_mode = CountMode::Tenth;
+        discipline.parts.push(match_part);
+        let mut session_a = Session::new("a".to_string(), Line::demo(), discipline.clone());
+        let mut session_b = Session::new("b".to_string(), Line::demo(), discipline);
+        session_a.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session_b.set_part("match".to_string(), true);
+        session_b.add_shot_raw(ShotRaw { x: 3000, y: 0 });
+        assert_eq!(None, session_a.get_result());
+        assert_eq!(Ordering::Greater, session_a.compare(&session_b));
+    }
+
+    #[test]
+    fn test_compare_tie_break() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].count_mode = CountMode::Integer;
+        let mut session_a = Session::new("a".to_string(), Line::demo(), discipline.clone());
+        let mut session_b = Session::new("b".to_string(), Line::demo(), discipline.clone());
+
+        // Both 20 rings, but only session_a has an inner ten
+        session_a.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session_a.add_shot_raw(ShotRaw { x: 2200, y: 0 });
+        session_b.add_shot_raw(ShotRaw { x: 2200, y: 0 });
+        session_b.add_shot_raw(ShotRaw { x: 0, y: 2200 });
+        assert_eq!(Ordering::Equal, session_a.compare(&session_b));
+
+        session_a.discipline.tie_break = vec![TieBreak::Total, TieBreak::LastSeries, TieBreak::InnerTens];
+        assert_eq!(Ordering::Less, session_a.compare(&session_b));
+        session_b.discipline.tie_break = vec![TieBreak::Total, TieBreak::BestTeiler];
+        assert_eq!(Ordering::Greater, session_b.compare(&session_a));
+    }
+
+    #[test]
+    fn test_invalidate_shot() {
+        let mut session = get_session();
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 2500, y: 0 });
+        assert_eq!(Score::from(20.9), session.sum.value);
+
+        session.invalidate_shot(0);
+        assert_eq!(Score::from(10.0), session.sum.value);
+        assert_eq!(1, session.number_of_shots);
+        assert_eq!(1, session.parts[0].series[0].shots[0].id);
+        assert_eq!(1, session.parts[0].series[0].shots[0].number);
+
+        // Shots can only be invalidated once
+        session.invalidate_shot(0);
+        session.invalidate_shot(5);
+        assert_eq!(3, session.events.len());
+
+        session.undo();
+        assert_eq!(Score::from(20.9), session.sum.value);
+        assert_eq!(2, session.parts[0].series[0].shots.len());
+    }
+
+    #[test]
+    fn test_penalty() {
+        let mut session = get_session();
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.add_penalty(0, Score::from_integer(2), "Late shot".to_string(), "Range officer".to_string());
+        assert_eq!(Score::from(10.9), session.sum.value);
+        assert_eq!(Score::from(8.9), session.net_sum.value);
+        assert_eq!(Score::from(10.9), session.parts[0].get_sum());
+        assert_eq!(Score::from(8.9), session.parts[0].get_net_sum());
+        assert_eq!(Some(Score::from(8.9)), session.get_result());
+
+        // Penalties stay deducted from later shots
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        assert_eq!(Some(Score::from(19.8)), session.get_result());
+
+        // Invalid penalties are not recorded
+        let number_of_events = session.events.len();
+        session.add_penalty(0, Score::from_integer(-1), "".to_string(), "".to_string());
+        session.add_penalty(5, Score::from_integer(1), "".to_string(), "".to_string());
+        assert_eq!(number_of_events, session.events.len());
+
+        session.undo();
+        assert_eq!(Some(Score::from(21.8)), session.get_result());
+        assert!(session.parts[0].penalties.is_empty());
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.time = Time::InstantStart { duration: 10 };
+        let created = SystemTime::now();
+        let at = |secs| created + Duration::from_secs(secs);
+        let mut session = Session::new_at("0".to_string(), Line::demo(), discipline, created);
+        assert_eq!(Some(at(600)), session.get_time_limit_end(at(0)));
+
+        session.push_event_at(Event::TimerPaused { reason: None }, at(60));
+        assert!(session.is_paused());
+        assert_eq!(Some(at(630)), session.get_time_limit_end(at(90)));
+        session.push_event_at(Event::TimerPaused { reason: None }, at(70));
+        assert_eq!(1, session.pauses.len());
+
+        session.push_event_at(Event::TimerResumed, at(120));
+        assert!(!session.is_paused());
+        assert_eq!(Some(at(660)), session.get_time_limit_end(at(300)));
+
+        session.push_event_at(Event::TimerResumed, at(130));
+        assert_eq!(2, session.events.len());
+    }
+
+    #[test]
+    fn test_final_late_shot() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        let stage = FinalStage {
+            name: "Series".to_string(), number_of_shots: 4, series_length: 2,
+            series_time: 100, sighting_time: None, eliminations: 0,
+        };
+        discipline.parts[0].mode = PartMode::Final { stages: vec![stage] };
+        let created = SystemTime::now();
+        let at = |secs| created + Duration::from_secs(secs);
+        let mut session = Session::new_at("0".to_string(), Line::demo(), discipline, created);
+
+        // The time of the first series is over before the second shot, it is a miss
+        session.push_event_at(Event::ShotAdded { x: 0, y: 0 }, at(50));
+        session.push_event_at(Event::ShotAdded { x: 0, y: 0 }, at(120));
+        let shots = &session.parts[0].series[0].shots;
+        assert!(!shots[0].late);
+        assert!(shots[1].late && shots[1].annulled);
+        assert_eq!(Score::from(10.9), session.sum.value);
+
+        // The next series has its own time
+        session.push_event_at(Event::ShotAdded { x: 0, y: 0 }, at(150));
+        assert!(!session.parts[0].series[1].shots[0].late);
+        assert_eq!(Score::from(21.8), session.sum.value);
+    }
+
+    #[test]
+    fn test_final_pause() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        let stage = FinalStage {
+            name: "Series".to_string(), number_of_shots: 2, series_length: 2,
+            series_time: 100, sighting_time: Some(60), eliminations: 0,
+        };
+        discipline.parts[0].mode = PartMode::Final { stages: vec![stage] };
+        let created = SystemTime::now();
+        let at = |secs| created + Duration::from_secs(secs);
+        let mut session = Session::new_at("0".to_string(), Line::demo(), discipline, created);
+
+        // The sighting period is stopped during the range stop
+        session.push_event_at(Event::TimerPaused { reason: None }, at(30));
+        session.push_event_at(Event::ShotAdded { x: 0, y: 0 }, at(80));
+        session.push_event_at(Event::TimerResumed, at(100));
+        session.push_event_at(Event::ShotAdded { x: 0, y: 0 }, at(120));
+        assert!(!session.get_active_part().is_counting());
+
+        session.push_event_at(Event::ShotAdded { x: 0, y: 0 }, at(140));
+        assert!(session.get_active_part().is_counting());
+        assert_eq!(1, session.number_of_shots);
+    }
+
+    #[test]
+    fn test_game() {
+        let game = Game {
+            id: "best_of_2".to_string(),
+            title: "Best of 2".to_string(),
+            rules: GameRules::BestOf { number_of_shots: 2 },
+        };
+        let mut session = Session::new("0".to_string(), Line::demo(), helper::dsc_demo::lg_discipline());
+        // Shots before the start are not played
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.start_game(game);
+        session.add_shot_raw(ShotRaw { x: 2000, y: 0 });
+        assert!(session.game.as_ref().unwrap().is_running());
+        assert_eq!(vec![2], session.game.as_ref().unwrap().hits);
+
+        // The game is played again, when the session is rebuilt
+        session.invalidate_shot(2);
+        assert_eq!(0, session.game.as_ref().unwrap().number_of_shots);
+        session.undo();
+        session.add_shot_raw(ShotRaw { x: 3000, y: 0 });
+        assert!(!session.game.as_ref().unwrap().is_running());
+        assert_eq!("Best 10.2 (2 of 2 shots)", session.game.as_ref().unwrap().text);
+
+        session.stop_game();
+        assert!(session.game.is_none());
+        session.undo();
+        assert!(session.game.is_some());
+    }
+
+    #[test]
+    fn test_runs() {
+        let slow_left = Run { speed: RunSpeed::Slow, direction: RunDirection::Left };
+        let slow_right = Run { speed: RunSpeed::Slow, direction: RunDirection::Right };
+        let fast_left = Run { speed: RunSpeed::Fast, direction: RunDirection::Left };
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].runs = vec![slow_left, slow_right];
+        let mut match_part = discipline.parts[0].clone();
+        match_part.id = "match".to_string();
+        match_part.runs = vec![];
+        discipline.parts.push(match_part);
+        let mut session = Session::new("0".to_string(), Line::demo(), discipline);
+
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.select_run(Some(fast_left));
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        let runs: Vec<Option<Run>> = session.parts[0].series[0].shots.iter()
+            .map(|shot| shot.run)
+            .collect();
+        assert_eq!(vec![Some(slow_left), Some(slow_right), Some(slow_left), Some(fast_left)], runs);
+
+        session.set_shot_run(0, fast_left);
+        assert_eq!(Some(fast_left), session.parts[0].series[0].shots[0].run);
+        let analysis = session.get_run_analysis();
+        assert_eq!(1, analysis.directions[1].number_of_shots);
+        assert_eq!(Score::from(21.8), analysis.fast.value);
+
+        session.undo();
+        assert_eq!(Some(slow_left), session.parts[0].series[0].shots[0].run);
+
+        // The selected run does not carry over to the next part
+        session.set_part("match".to_string(), true);
+        assert_eq!(None, session.selected_run);
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        assert_eq!(None, session.parts[1].series[0].shots[0].run);
+    }
+
+    #[test]
+    fn test_faces() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].count_mode = CountMode::Integer;
+        discipline.parts[0].series_length = 3;
+        discipline.parts[0].one_hit_per_face = true;
+        discipline.faces = vec![
+            TargetFace { name: "1".to_string(), x: -100_000, y: 0 },
+            TargetFace { name: "2".to_string(), x: 0, y: 0 },
+            TargetFace { name: "3".to_string(), x: 100_000, y: 0 },
+        ];
+        let mut session = Session::new("0".to_string(), Line::demo(), discipline);
+
+        session.add_shot_raw(ShotRaw { x: -100_000, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 102_500, y: 0 });
+        // Second hit on face 1 in the series
+        session.add_shot_raw(ShotRaw { x: -99_000, y: 0 });
+        // Next series
+        session.add_shot_raw(ShotRaw { x: -100_000, y: 0 });
+
+        let shots = &session.parts[0].series[0].shots;
+        let faces: Vec<usize> = shots.iter().map(|shot| shot.face).collect();
+        assert_eq!(vec![0, 2, 0], faces);
+        assert_eq!((2500, 0), (shots[1].x, shots[1].y));
+        assert!(shots[2].annulled);
+        assert_eq!(Score::from_integer(0), shots[2].ring_count);
+        assert_eq!(Score::from_integer(10) + shots[1].ring_count, session.parts[0].series[0].get_sum());
+        assert!(!session.parts[0].series[1].shots[0].annulled);
+        assert_eq!(Score::from_integer(20) + shots[1].ring_count, session.sum.value);
+
+        let statistics = session.get_face_statistics();
+        assert_eq!(3, statistics.len());
+        assert_eq!(3, statistics[0].number_of_shots);
+        assert_eq!(1, statistics[0].number_of_annulled);
+        assert_eq!(333, statistics[0].mpi_x);
+        assert_eq!(0, statistics[1].number_of_shots);
+    }
+
+    #[test]
+    fn test_faces_teiler() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].count_mode = CountMode::Teiler;
+        discipline.parts[0].one_hit_per_face = true;
+        discipline.faces = vec![
+            TargetFace { name: "1".to_string(), x: -100_000, y: 0 },
+            TargetFace { name: "2".to_string(), x: 100_000, y: 0 },
+        ];
+        let mut session = Session::new("0".to_string(), Line::demo(), discipline);
+
+        // The second hit on face 1 is a miss, and not the best teiler
+        session.add_shot_raw(ShotRaw { x: -99_000, y: 0 });
+        session.add_shot_raw(ShotRaw { x: -100_000, y: 0 });
+        let shot = &session.parts[0].series[0].shots[1];
+        assert!(shot.annulled);
+        assert_eq!(session.discipline.target.get_miss_teiler(), shot.ring_count);
+        assert_eq!(Score::from_integer(100), session.sum.value);
+
+        // Annulled inner tens are no tie break, both have the same teiler and one inner ten
+        let mut other = Session::new("1".to_string(), Line::demo(), session.discipline.clone());
+        other.add_shot_raw(ShotRaw { x: -99_000, y: 0 });
+        other.add_shot_raw(ShotRaw { x: 103_000, y: 0 });
+        assert!(!other.parts[0].series[0].shots[1].annulled);
+        session.discipline.tie_break = vec![TieBreak::InnerTens];
+        assert_eq!(Ordering::Equal, session.compare(&other));
+
+        // The total is compared before the tie break rules, other has more inner tens
+        let mut other = Session::new("2".to_string(), Line::demo(), session.discipline.clone());
+        other.add_shot_raw(ShotRaw { x: -98_500, y: 0 });
+        other.add_shot_raw(ShotRaw { x: 101_600, y: 0 });
+        assert_eq!(Ordering::Less, session.compare(&other));
+    }
+
+    #[test]
+    fn test_move_shots() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        let mut match_part = discipline.parts[0].clone();
+        match_part.id = "match".to_string();
+        match_part.count_mode = CountMode::Integer;
+        match_part.series_length = 2;
+        discipline.parts.push(match_part);
+        let mut session = Session::new("0".to_string(), Line::demo(), discipline);
+
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 2500, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 5000, y: 0 });
+        session.set_part("match".to_string(), true);
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+
+        // Move the last two probe shots to the match
+        session.move_shots(0, 2, 3, 1);
+        assert_eq!(1, session.parts[0].series[0].shots.len());
+        assert_eq!(2, session.parts[1].series.len());
+        let numbers: Vec<(i32, usize)> = session.parts[1].series.iter()
+            .flat_map(|series| series.shots.iter())
+            .map(|shot| (shot.number, shot.id))
+            .collect();
+        assert_eq!(vec![(1, 1), (2, 2), (3, 4)], numbers);
+        assert_eq!(Score::from_integer(10), session.parts[1].series[0].shots[0].ring_count);
+        assert_eq!(Score::from(39.9), session.sum.value);
+
+        // Invalid moves are not recorded
+        let number_of_events = session.events.len();
+        session.move_shots(0, 5, 6, 1);
+        session.move_shots(0, 1, 1, 3);
+        assert_eq!(number_of_events, session.events.len());
+
+        session.undo();
+        assert_eq!(3, session.parts[0].series[0].shots.len());
+        assert_eq!(1, session.parts[1].series[0].shots.len());
+    }
+
+    #[test]
+    fn test_undo_part_change() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        let mut match_part = discipline.parts[0].clone();
+        match_part.id = "match".to_string();
+        discipline.parts.push(match_part);
+        let mut session = Session::new("0".to_string(), Line::demo(), discipline);
+
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.set_part("match".to_string(), false);
+        session.add_shot_raw(ShotRaw { x: 2500, y: 0 });
+        assert_eq!(2, session.parts.len());
+        assert_eq!(1, session.active_part);
+
+        // Undo the part change, the shot stays and is moved to the probe part
+        session.undo();
+        assert_eq!(1, session.parts.len());
+        assert_eq!(0, session.active_part);
+        assert_eq!(2, session.parts[0].series[0].shots.len());
+
+        // Nothing left to undo
+        session.undo();
+        assert_eq!(4, session.events.len());
+    }
+
+    #[test]
+    fn test_set_user() {
+        let mut session = get_session();
+        let user = User { first_name: "Max".to_string(), last_name: "Mustermann".to_string(), id: "1".to_string() };
+        session.set_user(user);
+        session.set_club(Club { name: "SV Musterdorf".to_string(), id: "1".to_string() });
+        assert_eq!("Max", session.info.user.first_name);
+        assert_eq!("SV Musterdorf", session.info.club.name);
+
+        // Assignments are operator actions, which can be undone
+        session.undo();
+        assert_eq!("", session.info.club.name);
+        assert_eq!("Max", session.info.user.first_name);
+    }
+
+    #[test]
+    fn test_shared_line() {
+        let mut session = get_session();
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+
+        let user = User { first_name: "Erika".to_string(), last_name: "Musterfrau".to_string(), id: "".to_string() };
+        let club = Club { name: "SV Musterdorf".to_string(), id: "1".to_string() };
+        session.add_shooter(user, club, Team::empty());
+        assert_eq!(1, session.active_shooter);
+        assert_eq!("Erika", session.info.user.first_name);
+        assert_eq!("SV Musterdorf", session.info.club.name);
+        assert_eq!(0, session.parts[0].series[0].shots.len());
+        session.add_shot_raw(ShotRaw { x: 2500, y: 0 });
+        session.add_shot_raw(ShotRaw { x: 2500, y: 0 });
+
+        session.switch_shooter(0);
+        assert_eq!("Guest", session.info.user.first_name);
+        assert_eq!(1, session.parts[0].series[0].shots.len());
+        assert_eq!(Score::from(10.9), session.sum.value);
+        assert_eq!(1, session.other_shooters.len());
+        assert_eq!(2, session.other_shooters[0].number_of_shots);
+
+        // Unknown shooters are ignored, the state survives a rebuild
+        session.switch_shooter(5);
+        session.rebuild();
+        assert_eq!(0, session.active_shooter);
+        assert_eq!(1, session.parts[0].series[0].shots.len());
+        assert_eq!(2, session.other_shooters[0].parts[0].series[0].shots.len());
+    }
+
+    #[test]
+    fn test_rebuild() {
+        let mut session = get_session();
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.new_target();
+        session.add_shot_raw(ShotRaw { x: 3000, y: 1000 });
+        let sum = session.sum.value;
+
+        session.rebuild();
+        assert_eq!(sum, session.sum.value);
+        assert_eq!(2, session.number_of_shots);
+        assert_eq!(2, session.parts[0].series.len());
+    }
+
+    #[test]
+    fn test_legacy_session() {
+        // Session file stored before the event log was introduced
+        let text = include_str!("test_data/baseline.dscSession");
+        let mut session: Session = serde_json::from_str(text).unwrap();
+        assert!(session.events.is_empty());
+        session.rebuild();
+
+        assert_eq!(3, session.events.len());
+        assert_eq!(3, session.number_of_shots);
+        let shots = &session.parts[0].series[0].shots;
+        assert_eq!(vec![0, 1, 2], shots.iter().map(|shot| shot.id).collect::<Vec<usize>>());
+        assert_eq!((2500, 0), (shots[1].x, shots[1].y));
+        assert!(session.get_created() > UNIX_EPOCH && session.get_created() <= shots[0].get_date());
+        assert!(!session.finished);
+
+        // New shots are added to the migrated log
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        assert_eq!(4, session.number_of_shots);
+        session.rebuild();
+        assert_eq!(4, session.number_of_shots);
+    }
+
+    #[test]
+    fn test_legacy_session_series() {
+        // Probe with a new target after two shots and a shot after switching back from the match
+        let text = include_str!("test_data/baseline_series.dscSession");
+        let mut session: Session = serde_json::from_str(text).unwrap();
+        session.rebuild();
+
+        let positions = |part: &Part| -> Vec<Vec<i32>> {
+            part.series.iter().map(|series| series.shots.iter().map(|shot| shot.x).collect()).collect()
+        };
+        assert_eq!(2, session.parts.len());
+        assert_eq!(vec![vec![0, 1000], vec![2000, 5000]], positions(&session.parts[0]));
+        assert_eq!(vec![vec![3000, 4000]], positions(&session.parts[1]));
+        assert_eq!(0, session.active_part);
+
+        // The shots are added in the order they were shot
+        let ids: Vec<usize> = session.parts.iter()
+            .flat_map(|part| part.series.iter().flat_map(|series| series.shots.iter()))
+            .map(|shot| shot.id)
+            .collect();
+        let dates: Vec<SystemTime> = ids.iter().map(|id| session.events[*id].date).collect();
+        assert!(ids[3] > ids[4]);
+        assert!(dates[3] > dates[4]);
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut session = get_session();
+        session.add_shot_raw(ShotRaw { x: 0, y: 0 });
+        session.resume();
+        session.finish();
+        session.finish();
+        assert_eq!(3, session.events.len());
+
+        // Resume and finish can not be undone
+        session.undo();
+        assert_eq!(3, session.events.len());
+
+        session.rebuild();
+        assert!(session.resumed);
+        assert!(session.finished);
+        assert_eq!(1, session.number_of_shots);
+    }
+
     #[test]
     fn test_add_part() {
         let mut session = get_session();
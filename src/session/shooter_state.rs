@@ -0,0 +1,21 @@
+use std::time::SystemTime;
+
+use super::{Counter, Part, ActivePart, User, Club, Team};
+
+
+
+/// Parts and results of a shooter on a shared line, who is not active at the moment. The active
+/// shooter uses the fields of the session itself.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShooterState {
+    /// Number of the shooter in the session, the first shooter has number 0
+    pub number: usize,
+    pub user: User,
+    pub club: Club,
+    pub team: Team,
+    pub parts: Vec<Part>,
+    pub active_part: ActivePart,
+    pub sum: Counter,
+    pub number_of_shots: i32,
+    pub date: Option<SystemTime>,
+}
@@ -3,7 +3,7 @@ use std::time::SystemTime;
 
 use helper::round_to_one::RoundToOne;
 use discipline::*;
-use super::CountMode;
+use super::{CountMode, Score};
 
 
 
@@ -48,21 +48,38 @@ impl ShotRaw {
 /// Represents a single shot, with all its metadata.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Shot {
-    pub teiler: f64,
+    /// Distance to the center in 1/100 mm
+    pub teiler: Score,
     pub angle: f64,
     pub x: i32,
     pub y: i32,
 
     /// the actual ring, always with tenths
-    pub ring: f64,
+    pub ring: Score,
     pub ring_text: String,
     /// the counted part of the ring, e.g
     /// - no tenth 10.3 => ring_count = 10
     /// - tenth 10.3 => ring_count = 10.3
-    pub ring_count: f64,
+    /// - teiler modes => ring_count = teiler * teiler factor of the target
+    pub ring_count: Score,
     
     pub is_inner_ten: bool,
     pub number: i32,
+    /// Index of the event which added the shot to the session
+    #[serde(default)]
+    pub id: usize,
+    /// Run of the moving target, if the shot was fired at a moving target
+    #[serde(default)]
+    pub run: Option<Run>,
+    /// Index of the face the shot hit, on lanes with several faces
+    #[serde(default)]
+    pub face: usize,
+    /// True if the shot is scored as a miss, e.g. a second hit on a face in one series
+    #[serde(default)]
+    pub annulled: bool,
+    /// True if the shot was fired after the series time of a final was over, it is annulled
+    #[serde(default)]
+    pub late: bool,
 
     date: SystemTime,
 }
@@ -71,8 +88,33 @@ pub struct Shot {
 
 impl Shot {
 
-    pub fn from_raw(raw: ShotRaw, target: &Target, count_mode: &CountMode) -> Shot {
-        Shot::from_cartesian_coordinates(raw.x, raw.y, target, count_mode)
+    /// New shot from a raw shot
+    /// raw:                raw shot from the device
+    /// date:               date of the shot
+    /// target:             Target to use to calculate ring
+    /// count_mode          CountMode to use
+    pub fn from_raw(raw: ShotRaw, date: SystemTime, target: &Target, count_mode: &CountMode) -> Shot {
+        let mut shot = Shot::from_cartesian_coordinates(raw.x, raw.y, target, count_mode);
+        shot.date = date;
+        shot
+    }
+
+    /// Return the date of the shot
+    pub fn get_date(&self) -> SystemTime {
+        self.date
+    }
+
+    /// Score the shot as a miss, e.g. a second hit on a face in one series. In the teiler count
+    /// modes a miss counts with the teiler of the outer edge of the target, so it is never the
+    /// best shot.
+    /// target:             Target the shot was scored with
+    /// count_mode          CountMode to use
+    pub fn annul(&mut self, target: &Target, count_mode: &CountMode) {
+        self.annulled = true;
+        self.ring_count = match *count_mode {
+            CountMode::Integer | CountMode::Tenth => Score::zero(),
+            CountMode::Teiler | CountMode::TeilerSum => target.get_miss_teiler().scale(target.get_teiler_factor()),
+        };
     }
 
     /// New shot from x and y coordinates in 1/1000 mm
@@ -85,8 +127,8 @@ impl Shot {
         let y_f64 = y as f64;
 
         // Calculate teiler from cartesian coordinates (pythagoras),
-        // and round it to one decimal digit
-        let teiler = ((x_f64.powi(2) + y_f64.powi(2)).sqrt() / 10_f64).round_to_one();
+        // and round it to one decimal digit of 1/100 mm
+        let teiler = Score::from_tenths((x_f64.powi(2) + y_f64.powi(2)).sqrt().round() as i64);
 
         // Get the angle, and round it to one decimal digit.
         // Then move the range from [-180, 180] to [0, 360].
@@ -96,43 +138,54 @@ impl Shot {
         }
 
         let ring = Shot::get_ring_from_teiler(teiler, target);
-        let ring_count: f64 = match count_mode {
+        let ring_count = match count_mode {
             &CountMode::Integer => ring.floor(),
             &CountMode::Tenth => ring,
+            &CountMode::Teiler | &CountMode::TeilerSum => teiler.scale(target.get_teiler_factor()),
         };
 
-        let ring_text = format!("{:.1}", ring);
+        let ring_text = ring.to_string();
         
-        let is_inner_ten = teiler <= f64::from(target.inner_ten);
+        let is_inner_ten = teiler <= Score::from_integer(i64::from(target.inner_ten));
         let number = 0; // We set the number later
+        let id = 0; // We set the id later
 
         let date = SystemTime::now();
-        return Shot {teiler, angle, x, y, ring, ring_text, ring_count, is_inner_ten, number, date};
+        return Shot {teiler, angle, x, y, ring, ring_text, ring_count, is_inner_ten, number, id, run: None,
+            face: 0, annulled: false, late: false, date};
     }
 
-    /// Helper to calculate the actual ring for a given teiler
+    /// Helper to calculate the actual ring for a given teiler, based on the ring table of the
+    /// target. The ring is the innermost ring the shot touches, the decimal is interpolated
+    /// inside the width of this ring. The innermost ring uses the width of the next ring for its
+    /// decimals and is capped at the max ring of the target.
     /// teiler:     Teiler of the shot (1/100mm)
     /// target:     Target to use
-    fn get_ring_from_teiler(teiler: f64, target: &Target) -> f64 {
-        let ring_big = target.rings.first().unwrap();
-        let ring_small = target.rings.last().unwrap();
-        let k = target.bullet_diameter * 100_f64 / 2_f64;
-
-        let ring: f64;
-        // If its 0, its a 10.9, not an 11
-        if teiler == 0_f64 {
-            ring = 10.9_f64;
-        }
-        // If it is smaller than the smallest ring, its a 0
-        else if teiler > ring_small.width * 100_f64 / 2_f64 + k {
-            ring = 0_f64;
-        }
-        else {
-            let m = ((ring_big.value - ring_small.value) as f64) / (ring_big.width*100_f64/2_f64 - ring_small.width*100_f64/2_f64);
-            let t =  (ring_big.value as f64) - m * (ring_big.width*100_f64/2_f64 + k);
-            ring = (m * teiler + t).cut_at_one();
+    fn get_ring_from_teiler(teiler: Score, target: &Target) -> Score {
+        // Everything in 1/1000mm, which are the tenths of the teiler
+        let teiler = teiler.tenths();
+        // bullet radius
+        let k = target.bullet_diameter * 1000_f64 / 2_f64;
+        // outer edge of each ring, measured to the center of the shot
+        let edges: Vec<i64> = target.rings.iter()
+            .map(|ring| (ring.width * 1000_f64 / 2_f64 + k).round() as i64)
+            .collect();
+
+        for (i, ring) in target.rings.iter().enumerate() {
+            if teiler <= edges[i] {
+                let ring_width = match i {
+                    0 if edges.len() > 1 => edges[1] - edges[0],
+                    0 => edges[0],
+                    _ => edges[i] - edges[i - 1],
+                };
+                let tenths = ((edges[i] - teiler) * 10).checked_div(ring_width).unwrap_or(0);
+                let ring = Score::from_tenths(i64::from(ring.value) * 10 + tenths);
+                return ring.min(target.get_max_ring());
+            }
         }
-        return ring;
+
+        // If it is outside of the outermost ring, its a 0
+        return Score::zero();
     }
 }
 
@@ -161,54 +214,116 @@ mod test {
     fn test_zero_teiler() {
         let target = helper::dsc_demo::lg_target();
         let shot = Shot::from_cartesian_coordinates (0, 0, &target, &CountMode::Integer);
-        assert_eq!(0_f64, shot.teiler);
+        assert_eq!(Score::from(0.0), shot.teiler);
         assert_eq!(0_f64, shot.angle);
         assert_eq!(0_i32, shot.x);
         assert_eq!(0_i32, shot.y);
-        assert_eq!(10.9_f64, shot.ring);
+        assert_eq!(Score::from(10.9), shot.ring);
     }
 
     #[test]
     fn test_last_ten_1() {
         let target = helper::dsc_demo::lg_target();
         let shot = Shot::from_cartesian_coordinates (2500, 0, &target, &CountMode::Integer);
-        assert_eq!(250.0_f64, shot.teiler);
+        assert_eq!(Score::from(250.0), shot.teiler);
         assert_eq!(0_f64, shot.angle);
         assert_eq!(2500_i32, shot.x);
         assert_eq!(0_i32, shot.y);
-        assert_eq!(10.0_f64, shot.ring);
+        assert_eq!(Score::from(10.0), shot.ring);
     }
 
     #[test]
     fn test_last_ten_2() {
         let target = helper::dsc_demo::lg_target();
         let shot = Shot::from_cartesian_coordinates (0, 2500, &target, &CountMode::Integer);
-        assert_eq!(250.0_f64, shot.teiler);
+        assert_eq!(Score::from(250.0), shot.teiler);
         assert_eq!(90_f64, shot.angle);
         assert_eq!(0_i32, shot.x);
         assert_eq!(2500_i32, shot.y);
-        assert_eq!(10.0_f64, shot.ring);
+        assert_eq!(Score::from(10.0), shot.ring);
     }
 
     #[test]
     fn test_first_nine() {
         let target = helper::dsc_demo::lg_target();
         let shot = Shot::from_cartesian_coordinates (2501, 0, &target, &CountMode::Integer);
-        assert_eq!(250.1_f64, shot.teiler);
+        assert_eq!(Score::from(250.1), shot.teiler);
         assert_eq!(0_f64, shot.angle);
         assert_eq!(2501_i32, shot.x);
         assert_eq!(0_i32, shot.y);
-        assert_eq!(9.9_f64, shot.ring);
+        assert_eq!(Score::from(9.9), shot.ring);
     }
 
     #[test]
     fn test_zero() {
         let target = helper::dsc_demo::lg_target();
         let shot = Shot::from_cartesian_coordinates (-1000000, 0, &target, &CountMode::Integer);
-        assert_eq!(100000_f64, shot.teiler);
+        assert_eq!(Score::from(100000.0), shot.teiler);
         assert_eq!(180_f64, shot.angle);
         assert_eq!(-1000000, shot.x);
         assert_eq!(0_i32, shot.y);
-        assert_eq!(0_f64, shot.ring);
+        assert_eq!(Score::from(0.0), shot.ring);
+    }
+
+    #[test]
+    fn test_teiler_count() {
+        let mut target = helper::dsc_demo::lg_target();
+        let shot = Shot::from_cartesian_coordinates (3000, 4000, &target, &CountMode::Teiler);
+        assert_eq!(Score::from(500.0), shot.teiler);
+        assert_eq!(Score::from(500.0), shot.ring_count);
+
+        target.teiler_factor = Some(0.38);
+        let shot = Shot::from_cartesian_coordinates (3000, 4000, &target, &CountMode::TeilerSum);
+        assert_eq!(Score::from(500.0), shot.teiler);
+        assert_eq!(Score::from(190.0), shot.ring_count);
+    }
+
+    #[test]
+    fn test_ring_decimals() {
+        let target = helper::dsc_demo::lg_target();
+        let shot = Shot::from_cartesian_coordinates (4750, 0, &target, &CountMode::Tenth);
+        assert_eq!(Score::from(9.1), shot.ring);
+        assert_eq!(Score::from(9.1), shot.ring_count);
+        let shot = Shot::from_cartesian_coordinates (1250, 0, &target, &CountMode::Tenth);
+        assert_eq!(Score::from(10.5), shot.ring);
+    }
+
+    #[test]
+    fn test_last_ring() {
+        let target = helper::dsc_demo::lg_target();
+        let shot = Shot::from_cartesian_coordinates (25000, 0, &target, &CountMode::Integer);
+        assert_eq!(Score::from(1.0), shot.ring);
+        let shot = Shot::from_cartesian_coordinates (25001, 0, &target, &CountMode::Integer);
+        assert_eq!(Score::from(0.0), shot.ring);
+    }
+
+    #[test]
+    fn test_non_uniform_rings() {
+        // Target with 3 rings, values 5 to 3 and different ring widths
+        let mut target = helper::dsc_demo::lg_target();
+        target.rings.truncate(3);
+        target.rings[0].value = 5;
+        target.rings[0].width = 10.0;
+        target.rings[1].value = 4;
+        target.rings[1].width = 20.0;
+        target.rings[2].value = 3;
+        target.rings[2].width = 60.0;
+        target.bullet_diameter = 0.0;
+        target.max_ring = None;
+
+        // center is capped at 5.9
+        let shot = Shot::from_cartesian_coordinates (0, 0, &target, &CountMode::Tenth);
+        assert_eq!(Score::from(5.9), shot.ring);
+        // edge of the inner ring
+        let shot = Shot::from_cartesian_coordinates (5000, 0, &target, &CountMode::Tenth);
+        assert_eq!(Score::from(5.0), shot.ring);
+        // middle of the ring 4 (500 to 1000)
+        let shot = Shot::from_cartesian_coordinates (7500, 0, &target, &CountMode::Tenth);
+        assert_eq!(Score::from(4.5), shot.ring);
+        // ring 3 is wider (1000 to 3000)
+        let shot = Shot::from_cartesian_coordinates (20000, 0, &target, &CountMode::Tenth);
+        assert_eq!(Score::from(3.5), shot.ring);
+        let shot = Shot::from_cartesian_coordinates (30001, 0, &target, &CountMode::Tenth);
+        assert_eq!(Score::from(0.0), shot.ring);
     }
 }
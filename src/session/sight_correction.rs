@@ -0,0 +1,107 @@
+use discipline::Sight;
+use super::Shot;
+
+
+
+/// Shots to calculate the mean point of impact for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SightScope {
+    /// Shots of the current series
+    Series,
+    /// Shots of the active part
+    Part,
+}
+
+
+
+/// Suggested sight correction to centre the group of shots
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SightCorrection {
+    /// Mean point of impact in 1/1000 mm, relative to the centre of the target
+    pub mean_x: i32,
+    pub mean_y: i32,
+    pub number_of_shots: usize,
+    /// Clicks to the right, negative values are clicks to the left
+    pub windage_clicks: i32,
+    /// Clicks up, negative values are clicks down
+    pub elevation_clicks: i32,
+}
+
+impl SightCorrection {
+    /// Calculate the mean point of impact of the given shots, and the clicks needed to move it to
+    /// the centre of the target.
+    /// shots:      shots of the group, annulled shots are skipped
+    /// sight:      sight to calculate the clicks for
+    /// return:     None if there are no valid shots, or the sight has no click value
+    pub fn new(shots: &[&Shot], sight: &Sight) -> Option<SightCorrection> {
+        let click_value = sight.get_click_value_at_target();
+        let shots: Vec<&&Shot> = shots.iter().filter(|shot| !shot.annulled).collect();
+        if shots.is_empty() || !(click_value > 0_f64) {
+            return None;
+        }
+
+        let number_of_shots = shots.len();
+        let mean_x = shots.iter().map(|shot| f64::from(shot.x)).sum::<f64>() / number_of_shots as f64;
+        let mean_y = shots.iter().map(|shot| f64::from(shot.y)).sum::<f64>() / number_of_shots as f64;
+
+        // Shot coordinates are given in 1/1000 mm, the click value in mm
+        let clicks = |mean: f64| (-mean / 1000_f64 / click_value).round() as i32;
+        Some(SightCorrection {
+            mean_x: mean_x.round() as i32,
+            mean_y: mean_y.round() as i32,
+            number_of_shots,
+            windage_clicks: clicks(mean_x),
+            elevation_clicks: clicks(mean_y),
+        })
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use session::sight_correction::*;
+    use session::{Shot, CountMode};
+    use discipline::Sight;
+    use helper;
+
+    #[test]
+    fn test_correction() {
+        let target = helper::dsc_demo::lg_target();
+        let shots: Vec<Shot> = vec![(1000, -2000), (2000, -3000), (3000, -2500)].into_iter()
+            .map(|(x, y)| Shot::from_cartesian_coordinates(x, y, &target, &CountMode::Tenth))
+            .collect();
+        let shots: Vec<&Shot> = shots.iter().collect();
+
+        // 0.5 mm per click at 20 m, target at 10 m => 0.25 mm per click
+        let sight = Sight { click_value: 0.5, reference_distance: 20_f64, distance: 10_f64 };
+        let correction = SightCorrection::new(&shots, &sight).unwrap();
+        assert_eq!(2000, correction.mean_x);
+        assert_eq!(-2500, correction.mean_y);
+        assert_eq!(3, correction.number_of_shots);
+        assert_eq!(-8, correction.windage_clicks);
+        assert_eq!(10, correction.elevation_clicks);
+
+        assert!(SightCorrection::new(&[], &sight).is_none());
+    }
+
+    #[test]
+    fn test_correction_annulled() {
+        let target = helper::dsc_demo::lg_target();
+        let mut shots: Vec<Shot> = vec![(1000, -2000), (3000, -3000), (-20000, 20000)].into_iter()
+            .map(|(x, y)| Shot::from_cartesian_coordinates(x, y, &target, &CountMode::Tenth))
+            .collect();
+        shots[2].annulled = true;
+        let shots: Vec<&Shot> = shots.iter().collect();
+
+        let sight = Sight { click_value: 0.5, reference_distance: 20_f64, distance: 10_f64 };
+        let correction = SightCorrection::new(&shots, &sight).unwrap();
+        assert_eq!(2000, correction.mean_x);
+        assert_eq!(-2500, correction.mean_y);
+        assert_eq!(2, correction.number_of_shots);
+
+        assert!(SightCorrection::new(&shots[2..], &sight).is_none());
+    }
+}
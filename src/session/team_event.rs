@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use super::{Session, User, Team, CountMode, Score};
+
+
+
+/// Definition of a team competition, the best results of the members of each team are added up
+/// to the team total.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TeamEvent {
+    pub id: String,
+    pub name: String,
+    /// Id of the discipline the members have to shoot
+    pub discipline: String,
+    /// Number of member results counting for the team total
+    pub counting_results: usize,
+    pub teams: Vec<TeamEntry>,
+    /// Tags the sessions of the members have to have, e.g. the name of the event
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Team taking part in a team event
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TeamEntry {
+    pub team: Team,
+    pub members: Vec<User>,
+}
+
+
+
+/// Result of one member of a team
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MemberResult {
+    pub user: User,
+    /// Session the result is taken from, None if the member has no session yet
+    pub session_id: Option<String>,
+    pub result: Option<Score>,
+    /// True if the result counts for the team total
+    pub counting: bool,
+}
+
+/// Standing of a team in a team event
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TeamStanding {
+    pub team: Team,
+    pub rank: usize,
+    pub total: Score,
+    /// Results of all members, best result first
+    pub members: Vec<MemberResult>,
+}
+
+
+
+/// Return the latest session of the user with the given discipline. On a shared line the user
+/// can be one of the other shooters, the session is returned as seen by the user, see
+/// Session::get_session_of_user.
+/// sessions:       sessions to search
+/// discipline:     id of the discipline
+/// user:           user to search the session for, users without id have no session
+pub fn latest_session(sessions: &[Session], discipline: &str, user: &User) -> Option<Session> {
+    sessions.iter()
+        .filter(|session| session.discipline.id == discipline)
+        .filter_map(|session| session.get_session_of_user(&user.id))
+        .max_by_key(|session| session.get_created())
+}
+
+
+
+/// Rank the teams of a team event. Each member contributes the result of its latest session of
+/// the discipline. Teams with the same total are ranked by comparing their counting results,
+/// best result first, with the tie break rules of the discipline.
+/// event:      definition of the team event
+/// sessions:   sessions of the members, sessions of other disciplines or users are skipped
+/// return:     standings, best team first
+pub fn rank_teams(event: &TeamEvent, sessions: &[Session]) -> Vec<TeamStanding> {
+    let count_mode = match sessions.iter().find(|session| session.discipline.id == event.discipline) {
+        Some(session) => session.get_count_mode(),
+        None => CountMode::Integer,
+    };
+
+    let mut standings: Vec<(TeamStanding, Vec<Session>)> = event.teams.iter()
+        .map(|entry| {
+            let mut members: Vec<(&User, Option<Session>)> = entry.members.iter()
+                .map(|user| (user, latest_session(sessions, &event.discipline, user)))
+                .collect();
+            members.sort_by(|a, b| match (a.1.as_ref(), b.1.as_ref()) {
+                (Some(a), Some(b)) => a.compare(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+
+            let counting: Vec<Session> = members.iter()
+                .filter_map(|member| member.1.as_ref())
+                .filter(|session| session.get_result().is_some())
+                .take(event.counting_results)
+                .cloned()
+                .collect();
+            let standing = TeamStanding {
+                team: entry.team.clone(),
+                rank: 0,
+                total: counting.iter().filter_map(|session| session.get_result()).sum(),
+                members: members.iter().enumerate()
+                    .map(|(i, &(user, ref session))| MemberResult {
+                        user: user.clone(),
+                        session_id: session.as_ref().map(|session| session.id.clone()),
+                        result: session.as_ref().and_then(|session| session.get_result()),
+                        counting: i < counting.len(),
+                    })
+                    .collect(),
+            };
+            (standing, counting)
+        })
+        .collect();
+
+    // A team without counting results is ranked last, also in the teiler modes
+    let total = |standing: &(TeamStanding, Vec<Session>)| match standing.1.is_empty() {
+        true => None,
+        false => Some(standing.0.total),
+    };
+    standings.sort_by(|a, b| {
+        count_mode.compare_results(total(a), total(b)).then_with(|| {
+            a.1.iter().zip(b.1.iter())
+                .map(|(a, b)| a.compare(b))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        })
+    });
+
+    standings.into_iter().enumerate()
+        .map(|(i, (mut standing, _))| {
+            standing.rank = i + 1;
+            standing
+        })
+        .collect()
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use session::team_event::*;
+    use session::{Session, Line, ShotRaw, AddShotRaw, Update, Club};
+    use discipline::TieBreak;
+    use helper;
+
+    fn user(id: &str) -> User {
+        User { first_name: id.to_string(), last_name: "".to_string(), id: id.to_string() }
+    }
+
+    fn session(id: &str, user_id: &str, shots: &[(i32, i32)]) -> Session {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].count_mode = CountMode::Integer;
+        discipline.tie_break = vec![TieBreak::Total, TieBreak::InnerTens];
+        let mut session = Session::new(id.to_string(), Line::demo(), discipline);
+        session.set_user(user(user_id));
+        for &(x, y) in shots {
+            session.add_shot_raw(ShotRaw { x, y });
+        }
+        session
+    }
+
+    fn event() -> TeamEvent {
+        let team = |id: &str, members: &[&str]| TeamEntry {
+            team: Team { name: id.to_string(), id: id.to_string() },
+            members: members.iter().map(|id| user(id)).collect(),
+        };
+        TeamEvent {
+            id: "0".to_string(),
+            name: "Team".to_string(),
+            discipline: helper::dsc_demo::lg_discipline().id,
+            counting_results: 2,
+            teams: vec![team("A", &["1", "2", "3"]), team("B", &["4", "5"])],
+            tags: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_rank_teams() {
+        let sessions = vec![
+            session("a", "1", &[(0, 0)]),
+            session("b", "2", &[(2500, 0)]),
+            session("c", "3", &[(5000, 0)]),
+            session("d", "4", &[(0, 0)]),
+        ];
+        let standings = rank_teams(&event(), &sessions);
+        assert_eq!("A", standings[0].team.id);
+        assert_eq!(Some("a".to_string()), standings[0].members[0].session_id);
+        assert!(standings[0].members[1].counting);
+        assert!(!standings[0].members[2].counting);
+        assert_eq!(standings[0].members[0].result.unwrap() + standings[0].members[1].result.unwrap(),
+            standings[0].total);
+
+        // Member without a session does not count
+        assert_eq!(2, standings[1].rank);
+        assert_eq!(None, standings[1].members[1].result);
+        assert!(!standings[1].members[1].counting);
+    }
+
+    #[test]
+    fn test_rank_teams_tie_break() {
+        // Same total, team B has an inner ten
+        let sessions = vec![
+            session("a", "1", &[(2200, 0)]),
+            session("b", "2", &[(0, 2200)]),
+            session("d", "4", &[(0, 0)]),
+            session("e", "5", &[(2200, 0)]),
+        ];
+        let event = event();
+        let standings = rank_teams(&event, &sessions);
+        assert_eq!(standings[0].total, standings[1].total);
+        assert_eq!("B", standings[0].team.id);
+    }
+
+    #[test]
+    fn test_rank_teams_shared_line() {
+        // Member 2 shoots as second shooter on the line of member 1
+        let mut shared = session("a", "1", &[(0, 0)]);
+        shared.add_shooter(user("2"), Club::empty(), Team::empty());
+        shared.add_shot_raw(ShotRaw { x: 2500, y: 0 });
+        shared.switch_shooter(0);
+
+        let standings = rank_teams(&event(), &[shared]);
+        let team = standings.iter().find(|standing| standing.team.id == "A").unwrap();
+        assert_eq!(Some(Score::from_integer(10)), team.members[0].result);
+        assert_eq!(Some(Score::from_integer(10)), team.members[1].result);
+        assert_eq!(Some("a".to_string()), team.members[1].session_id);
+        assert_eq!(Score::from_integer(20), team.total);
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::{SystemTime, Duration};
+
+
+
+/// Countdown with a start date and a duration, used for time limits (e.g. the series of a final).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Timer {
+    pub start: SystemTime,
+    /// Duration of the countdown in seconds
+    pub duration: u64,
+}
+
+impl Timer {
+    /// Start a new timer at the given date
+    /// start:      start date of the countdown
+    /// duration:   duration in seconds
+    pub fn new_at(start: SystemTime, duration: u64) -> Timer {
+        Timer { start, duration }
+    }
+
+    /// Return the remaining time of the countdown at the given date, the countdown is stopped
+    /// during the pauses
+    /// now:        current date
+    /// pauses:     pauses of the session, an open pause lasts until now
+    pub fn remaining(&self, now: SystemTime, pauses: &[Pause]) -> Duration {
+        let paused = pauses.iter()
+            .fold(Duration::from_secs(0), |sum, pause| sum + pause.duration_after(self.start, now));
+        let elapsed = now.duration_since(self.start).unwrap_or(Duration::from_secs(0));
+        let elapsed = elapsed.checked_sub(paused).unwrap_or(Duration::from_secs(0));
+        let duration = Duration::from_secs(self.duration);
+        match duration.checked_sub(elapsed) {
+            Some(remaining) => remaining,
+            None => Duration::from_secs(0),
+        }
+    }
+
+    /// Check if the countdown is over at the given date, see remaining
+    pub fn is_over(&self, now: SystemTime, pauses: &[Pause]) -> bool {
+        self.remaining(now, pauses) == Duration::from_secs(0)
+    }
+}
+
+
+
+/// Interval in which the time limits of a session were stopped, e.g. for a range incident
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pause {
+    pub start: SystemTime,
+    /// End of the pause, None while the session is paused
+    pub end: Option<SystemTime>,
+    pub reason: Option<String>,
+}
+
+impl Pause {
+    /// Return the duration of the pause after the given date, an open pause lasts until now
+    /// since:  date to count from, e.g. the start of a time limit
+    /// now:    current date
+    pub fn duration_after(&self, since: SystemTime, now: SystemTime) -> Duration {
+        let start = if self.start > since { self.start } else { since };
+        let end = self.end.unwrap_or(now);
+        end.duration_since(start).unwrap_or(Duration::from_secs(0))
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::time::{SystemTime, Duration};
+    use session::timer::{Timer, Pause};
+
+    #[test]
+    fn test_remaining() {
+        let start = SystemTime::now();
+        let timer = Timer::new_at(start, 50);
+        assert_eq!(Duration::from_secs(50), timer.remaining(start, &[]));
+        assert_eq!(Duration::from_secs(20), timer.remaining(start + Duration::from_secs(30), &[]));
+        assert!(!timer.is_over(start + Duration::from_secs(49), &[]));
+        assert!(timer.is_over(start + Duration::from_secs(50), &[]));
+        assert!(timer.is_over(start + Duration::from_secs(80), &[]));
+    }
+
+    #[test]
+    fn test_remaining_paused() {
+        let start = SystemTime::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let timer = Timer::new_at(at(10), 50);
+        let mut pauses = vec![Pause { start: at(0), end: Some(at(20)), reason: None }];
+        assert_eq!(Duration::from_secs(50), timer.remaining(at(20), &pauses));
+
+        // The countdown is frozen during an open pause
+        pauses.push(Pause { start: at(40), end: None, reason: None });
+        assert_eq!(Duration::from_secs(30), timer.remaining(at(100), &pauses));
+        assert!(!timer.is_over(at(1000), &pauses));
+    }
+
+    #[test]
+    fn test_pause_duration() {
+        let start = SystemTime::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let pause = Pause { start: at(10), end: Some(at(30)), reason: None };
+        assert_eq!(Duration::from_secs(20), pause.duration_after(start, at(100)));
+        assert_eq!(Duration::from_secs(5), pause.duration_after(at(25), at(100)));
+        assert_eq!(Duration::from_secs(0), pause.duration_after(at(40), at(100)));
+
+        // Open pause
+        let pause = Pause { start: at(10), end: None, reason: None };
+        assert_eq!(Duration::from_secs(15), pause.duration_after(start, at(25)));
+    }
+}
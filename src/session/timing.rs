@@ -0,0 +1,192 @@
+use std::time::{SystemTime, Duration};
+
+use super::{Part, PartType, Shot};
+
+
+
+/// Timing analysis of the shots of a session, used to show the rhythm of the shooter
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimingAnalysis {
+    /// Seconds between two shots of the same part, in the order of the shots
+    pub intervals: Vec<f64>,
+    /// Average of the intervals in seconds
+    pub average_interval: Option<f64>,
+    /// Variance of the intervals in seconds²
+    pub interval_variance: Option<f64>,
+    /// Time from the first to the last shot of each series
+    pub series: Vec<SeriesTiming>,
+    /// Pace of the active part compared to its remaining time
+    pub pace: Option<Pace>,
+    /// Correlation (-1 to 1) between the interval before a shot and its ring. A positive value
+    /// means better shots after longer breaks.
+    pub ring_interval_correlation: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SeriesTiming {
+    pub part_type: PartType,
+    /// Index of the series in its part
+    pub index: usize,
+    /// Seconds from the first to the last shot of the series
+    pub duration: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pace {
+    /// Seconds until the end of the time limit
+    pub remaining_time: f64,
+    pub remaining_shots: i32,
+    /// Seconds available for each remaining shot
+    pub time_per_shot: Option<f64>,
+    /// True if the average interval is longer than the time available for each remaining shot
+    pub is_behind: bool,
+}
+
+impl TimingAnalysis {
+    /// Analyse the counted shots of the given parts
+    /// parts:              parts of the session
+    /// time_limit_end:     end of the time limit of the active part, if there is one
+    /// remaining_shots:    number of shots left in the active part, if the number is limited
+    /// now:                date to calculate the remaining time for
+    pub fn new(parts: &[Part], time_limit_end: Option<SystemTime>, remaining_shots: Option<i32>, now: SystemTime) -> TimingAnalysis {
+        let mut intervals = vec![];
+        let mut rings = vec![];
+        let mut series_timings = vec![];
+        for part in parts {
+            let shots: Vec<&Shot> = part.series.iter().flat_map(|series| series.shots.iter()).collect();
+            for pair in shots.windows(2) {
+                intervals.push(seconds_between(pair[0].get_date(), pair[1].get_date()));
+                rings.push(pair[1].ring.to_f64());
+            }
+
+            for (index, series) in part.series.iter().enumerate() {
+                if let (Some(first), Some(last)) = (series.shots.first(), series.shots.last()) {
+                    series_timings.push(SeriesTiming {
+                        part_type: part.part_type.clone(),
+                        index,
+                        duration: seconds_between(first.get_date(), last.get_date()),
+                    });
+                }
+            }
+        }
+
+        let average_interval = mean(&intervals);
+        let pace = match (time_limit_end, remaining_shots) {
+            (Some(end), Some(remaining_shots)) => {
+                let remaining_time = seconds_between(now, end);
+                let time_per_shot = if remaining_shots > 0 {
+                    Some(remaining_time / f64::from(remaining_shots))
+                } else {
+                    None
+                };
+                let is_behind = match (average_interval, time_per_shot) {
+                    (Some(average), Some(available)) => average > available,
+                    _ => false,
+                };
+                Some(Pace { remaining_time, remaining_shots, time_per_shot, is_behind })
+            },
+            _ => None,
+        };
+
+        TimingAnalysis {
+            average_interval,
+            interval_variance: variance(&intervals),
+            series: series_timings,
+            pace,
+            ring_interval_correlation: correlation(&intervals, &rings),
+            intervals,
+        }
+    }
+}
+
+
+
+/// Seconds from start to end, 0 if end is before start
+fn seconds_between(start: SystemTime, end: SystemTime) -> f64 {
+    let duration = end.duration_since(start).unwrap_or(Duration::from_secs(0));
+    duration.as_secs() as f64 + f64::from(duration.subsec_millis()) / 1000_f64
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn variance(values: &[f64]) -> Option<f64> {
+    mean(values).map(|mean| {
+        values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64
+    })
+}
+
+/// Pearson correlation of two value lists with the same length, None if there are less than two
+/// values or one of the lists is constant
+fn correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() < 2 || a.len() != b.len() {
+        return None;
+    }
+    let (mean_a, mean_b) = (mean(a)?, mean(b)?);
+    let covariance: f64 = a.iter().zip(b).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum();
+    let deviation_a: f64 = a.iter().map(|x| (x - mean_a).powi(2)).sum::<f64>().sqrt();
+    let deviation_b: f64 = b.iter().map(|y| (y - mean_b).powi(2)).sum::<f64>().sqrt();
+    if deviation_a == 0_f64 || deviation_b == 0_f64 {
+        return None;
+    }
+    Some(covariance / (deviation_a * deviation_b))
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use std::time::{SystemTime, Duration};
+    use session::timing::*;
+    use session::{Part, Shot, ShotRaw, AddShot, CountMode};
+    use helper;
+
+    #[test]
+    fn test_statistics() {
+        assert_eq!(None, mean(&[]));
+        assert_eq!(Some(2_f64), mean(&[1_f64, 3_f64]));
+        assert_eq!(Some(1_f64), variance(&[1_f64, 3_f64]));
+        let positive = correlation(&[1_f64, 2_f64, 3_f64], &[2_f64, 4_f64, 6_f64]).unwrap();
+        assert!((positive - 1_f64).abs() < 1e-9);
+        let negative = correlation(&[1_f64, 2_f64, 3_f64], &[6_f64, 4_f64, 2_f64]).unwrap();
+        assert!((negative + 1_f64).abs() < 1e-9);
+        assert_eq!(None, correlation(&[1_f64, 1_f64], &[2_f64, 4_f64]));
+    }
+
+    #[test]
+    fn test_analysis() {
+        let mut discipline = helper::dsc_demo::lg_discipline();
+        discipline.parts[0].series_length = 2;
+        let discipline_part = discipline.parts[0].clone();
+
+        let start = SystemTime::now();
+        let mut part = Part::new(&discipline_part, start);
+        for &(seconds, x) in &[(0, 0), (30, 0), (90, 2500)] {
+            let date = start + Duration::from_secs(seconds);
+            let shot = Shot::from_raw(ShotRaw { x, y: 0 }, date, &discipline.target, &CountMode::Tenth);
+            part.add_shot(shot, &discipline, &CountMode::Tenth);
+        }
+
+        let end = start + Duration::from_secs(300);
+        let analysis = TimingAnalysis::new(&[part], Some(end), Some(3), start + Duration::from_secs(120));
+        assert_eq!(vec![30_f64, 60_f64], analysis.intervals);
+        assert_eq!(Some(45_f64), analysis.average_interval);
+        assert_eq!(Some(225_f64), analysis.interval_variance);
+        assert_eq!(2, analysis.series.len());
+        assert_eq!(30_f64, analysis.series[0].duration);
+        assert_eq!(0_f64, analysis.series[1].duration);
+        // Only two intervals, so the correlation is always -1 or 1
+        assert!((analysis.ring_interval_correlation.unwrap() + 1_f64).abs() < 1e-9);
+
+        let pace = analysis.pace.unwrap();
+        assert_eq!(180_f64, pace.remaining_time);
+        assert_eq!(Some(60_f64), pace.time_per_shot);
+        assert!(!pace.is_behind);
+    }
+}
@@ -190,8 +190,108 @@ fn process_message(manager: &DSCManagerMutex, message: String) {
                 RequestType::CheckPaper => {
                     manager.lock().unwrap().check_paper();
                 }
-                RequestType::GetStoredSessions{ since } => {
-                    let x = manager.lock().unwrap().get_stored_sessions(since);
+                RequestType::GetStoredSessions{ since, mut filter } => {
+                    filter.since = Some(since);
+                    manager.lock().unwrap().send_stored_sessions(filter);
+                }
+                RequestType::StartFinalStage => {
+                    manager.lock().unwrap().start_final_stage();
+                }
+                RequestType::GetFinalRanking{ session_ids } => {
+                    manager.lock().unwrap().send_final_ranking(session_ids);
+                }
+                RequestType::InvalidateShot{ shot_id } => {
+                    manager.lock().unwrap().invalidate_shot(shot_id);
+                }
+                RequestType::MoveShots{ from_part, first_shot, last_shot, to_part } => {
+                    manager.lock().unwrap().move_shots(from_part, first_shot, last_shot, to_part);
+                }
+                RequestType::AddPenalty{ part, amount, reason, officer } => {
+                    manager.lock().unwrap().add_penalty(part, amount, reason, officer);
+                }
+                RequestType::SelectRun{ run } => {
+                    manager.lock().unwrap().select_run(run);
+                }
+                RequestType::SetShotRun{ shot_id, run } => {
+                    manager.lock().unwrap().set_shot_run(shot_id, run);
+                }
+                RequestType::GetRunAnalysis => {
+                    manager.lock().unwrap().send_run_analysis();
+                }
+                RequestType::GetFaceStatistics => {
+                    manager.lock().unwrap().send_face_statistics();
+                }
+                RequestType::Pause{ reason } => {
+                    manager.lock().unwrap().pause(reason);
+                }
+                RequestType::Resume => {
+                    manager.lock().unwrap().resume_timer();
+                }
+                RequestType::StartGame{ name } => {
+                    manager.lock().unwrap().start_game_by_name(&name);
+                }
+                RequestType::StopGame => {
+                    manager.lock().unwrap().stop_game();
+                }
+                RequestType::Undo => {
+                    manager.lock().unwrap().undo();
+                }
+                RequestType::GetTimingAnalysis => {
+                    manager.lock().unwrap().send_timing_analysis();
+                }
+                RequestType::Finalize{ signed_off_by } => {
+                    manager.lock().unwrap().finalize_session(signed_off_by);
+                }
+                RequestType::VerifyFinalizedSessions => {
+                    manager.lock().unwrap().verify_finalized_sessions();
+                }
+                RequestType::GetLotRanking => {
+                    manager.lock().unwrap().send_lot_ranking();
+                }
+                RequestType::GetTeamStandings{ event_id } => {
+                    manager.lock().unwrap().send_team_standings(event_id);
+                }
+                RequestType::GetLeagueMatch{ match_id } => {
+                    manager.lock().unwrap().send_league_match(&match_id);
+                }
+                RequestType::PrintLeagueMatch{ match_id } => {
+                    manager.lock().unwrap().print_league_match(&match_id);
+                }
+                RequestType::GetSightCorrection{ scope } => {
+                    manager.lock().unwrap().send_sight_correction(scope);
+                }
+                RequestType::SetUser{ user } => {
+                    manager.lock().unwrap().set_user(user);
+                }
+                RequestType::SetClub{ club } => {
+                    manager.lock().unwrap().set_club(club);
+                }
+                RequestType::SetTeam{ team } => {
+                    manager.lock().unwrap().set_team(team);
+                }
+                RequestType::AddShooter{ user, club, team } => {
+                    manager.lock().unwrap().add_shooter(user, club, team);
+                }
+                RequestType::SwitchShooter{ shooter } => {
+                    manager.lock().unwrap().switch_shooter(shooter);
+                }
+                RequestType::SetTag{ key, value } => {
+                    manager.lock().unwrap().set_tag(key, value);
+                }
+                RequestType::SetEquipment{ equipment } => {
+                    manager.lock().unwrap().set_equipment(equipment);
+                }
+                RequestType::CreateShooter{ shooter } => {
+                    manager.lock().unwrap().create_shooter(shooter);
+                }
+                RequestType::UpdateShooter{ shooter } => {
+                    manager.lock().unwrap().update_shooter(shooter);
+                }
+                RequestType::SearchShooters{ query } => {
+                    manager.lock().unwrap().search_shooters(&query);
+                }
+                RequestType::SelectShooter{ shooter_id } => {
+                    manager.lock().unwrap().select_shooter(&shooter_id);
                 }
                 RequestType::ShutdownLine => {
                     Command::new("sudo")
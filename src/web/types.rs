@@ -1,7 +1,12 @@
 use std::sync::{Arc, Mutex, mpsc};
 use std::time::SystemTime;
 
-use session::Session;
+use session::{Session, FinalRank, LotRank, User, Club, Team, Equipment, TimingAnalysis,
+    SightCorrection, SightScope, TeamStanding, LeagueMatchResult, RunAnalysis,
+    FaceStatistics, Score};
+use discipline::Run;
+use registry::Shooter;
+use database::filter::SessionFilter;
 use config::Config as DSCConfig;
 
 
@@ -43,8 +48,109 @@ pub enum RequestType {
     /// Move the paper and check its movement
     CheckPaper,
     
-    /// Request all sessions since 
-    GetStoredSessions {since: SystemTime},
+    /// Request all stored sessions since the given date, matching the optional filter
+    GetStoredSessions {since: SystemTime, #[serde(default)] filter: SessionFilter},
+
+    /// End the sighting period of the active final stage and start counting
+    StartFinalStage,
+
+    /// Request the ranking of the final, with the sessions of the other lines
+    GetFinalRanking {session_ids: Vec<String>},
+
+    /// Invalidate the shot with the given id, it will no longer be counted
+    InvalidateShot {shot_id: usize},
+
+    /// Move the shots with the given numbers from one part to another
+    MoveShots {from_part: usize, first_shot: i32, last_shot: i32, to_part: usize},
+
+    /// Deduct points from the part with the given index, e.g. for a late shot
+    AddPenalty {part: usize, amount: Score, reason: String, officer: String},
+
+    /// Select the run of the moving target for the next shots, None to use the runs of the part
+    SelectRun {run: Option<Run>},
+
+    /// Correct the run of the shot with the given id
+    SetShotRun {shot_id: usize, run: Run},
+
+    /// Request the results of the active part split by the runs of the moving target
+    GetRunAnalysis,
+
+    /// Request the statistics of each face of the lane for the active part
+    GetFaceStatistics,
+
+    /// Stop the time limits of the current session, e.g. for a range incident
+    Pause {#[serde(default)] reason: Option<String>},
+
+    /// Continue the time limits of the current session after a pause
+    Resume,
+
+    /// Start the training game with the given id in the current session
+    StartGame {name: String},
+
+    /// Stop the training game of the current session
+    StopGame,
+
+    /// Undo the last operator action of the current session
+    Undo,
+
+    /// Request the timing analysis of the current session
+    GetTimingAnalysis,
+
+    /// Sign off the current session, it can not be changed afterwards
+    Finalize {signed_off_by: String},
+
+    /// Verify, that the finalized sessions of this line were not altered
+    VerifyFinalizedSessions,
+
+    /// Request the ranking of the lots of the current lot test
+    GetLotRanking,
+
+    /// Request the standings of the team event with the given id, the standings are sent again
+    /// after every change of the current session
+    GetTeamStandings {event_id: String},
+
+    /// Request the result of the league match with the given id
+    GetLeagueMatch {match_id: String},
+
+    /// Export the report of the league match with the given id
+    PrintLeagueMatch {match_id: String},
+
+    /// Request a sight correction for the current series or the active part
+    GetSightCorrection {scope: SightScope},
+
+    /// Set the user of the current session, users with id have to be in the roster
+    SetUser {user: User},
+
+    /// Set the club of the current session, clubs with id have to be in the roster
+    SetClub {club: Club},
+
+    /// Set the team of the current session, teams with id have to be in the roster
+    SetTeam {team: Team},
+
+    /// Add a shooter to the line, the new shooter becomes the active one. User, club and team with
+    /// id have to be in the roster.
+    AddShooter {user: User, #[serde(default="Club::empty")] club: Club, #[serde(default="Team::empty")] team: Team},
+
+    /// Change the active shooter of the line, by the number of the shooter
+    SwitchShooter {shooter: usize},
+
+    /// Set a tag of the current session, a tag without value is removed
+    SetTag {key: String, value: Option<String>},
+
+    /// Set the equipment of the current session
+    SetEquipment {equipment: Equipment},
+
+    /// Add a new shooter to the registry, the id is generated if empty
+    CreateShooter {shooter: Shooter},
+
+    /// Update the shooter with the same id in the registry
+    UpdateShooter {shooter: Shooter},
+
+    /// Search the registry, an empty query returns all shooters
+    SearchShooters {query: String},
+
+    /// Assign the shooter with the given id from the registry to the current session
+    SelectShooter {shooter_id: String},
     
     // Halt System
     ShutdownLine,
@@ -63,6 +169,36 @@ pub enum SendType {
     
     StoredSessions {sessions: Vec<Session>},
 
+    /// Ranking of a final with several lines
+    FinalRanking {ranking: Vec<FinalRank>},
+
+    /// Timing analysis of the current session
+    TimingAnalysis {analysis: TimingAnalysis},
+
+    /// Result of the verification of the finalized sessions
+    Verification {valid: bool, message: String},
+
+    /// Ranking of the lots of a lot test
+    LotRanking {ranking: Vec<LotRank>},
+
+    /// Live standings of a team event
+    TeamStandings {event_id: String, standings: Vec<TeamStanding>},
+
+    /// Result of a league match, with the duels of all positions
+    LeagueMatch {result: LeagueMatchResult},
+
+    /// Results of the active part split by the runs of the moving target
+    RunAnalysis {analysis: RunAnalysis},
+
+    /// Statistics of each face of the lane
+    FaceStatistics {statistics: Vec<FaceStatistics>},
+
+    /// Suggested sight correction for the current session
+    SightCorrection {correction: SightCorrection},
+
+    /// Shooters from the registry, e.g. the result of a search
+    Shooters {shooters: Vec<Shooter>},
+
     // Log message
     Log {log: Log}
 }